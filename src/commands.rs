@@ -0,0 +1,408 @@
+//! Tables printed by the subcommands, laid out like the output of `readelf`
+
+use std::fmt;
+use std::io::{self, Write};
+
+use elf_parser::elf::*;
+use elf_parser::elf::notes::gnu_note_type_name;
+
+/// Errors of the subcommands, which fail either to parse the file or to
+/// write their output
+#[derive(Debug)]
+pub enum Error {
+    Elf(ElfError),
+    Io(io::Error),
+}
+
+impl Error {
+    /// The output was closed by its reader, as `head` does once it has
+    /// read enough
+    pub fn is_broken_pipe(&self) -> bool {
+        matches!(self, Error::Io(e) if e.kind() == io::ErrorKind::BrokenPipe)
+    }
+}
+
+impl From<ElfError> for Error {
+    fn from(e : ElfError) -> Self {
+        Error::Elf(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e : io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Elf(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Demangled form of `name` when `demangle` is set and `name` is a mangled
+/// C++ or Rust name, for the names not held by a `Symbol`
+#[cfg_attr(not(feature = "demangle"), allow(unused_variables))]
+pub fn symbol_name(name : &str, demangle : bool) -> String {
+    #[cfg(feature = "demangle")]
+    if demangle {
+        if let Some(name) = elf_parser::elf::demangle::demangle(name) {
+            return name;
+        }
+    }
+    name.to_string()
+}
+
+/// Demangles the names of `symbols` when `demangle` is set
+#[cfg_attr(not(feature = "demangle"), allow(unused_mut, unused_variables))]
+pub fn demangle_symbols(mut symbols : Vec<Symbol>, demangle : bool)
+    -> Vec<Symbol>
+{
+    #[cfg(feature = "demangle")]
+    if demangle {
+        symbols.iter_mut().for_each(Symbol::demangle);
+    }
+    symbols
+}
+
+/// Prints the file header (`readelf -h`)
+pub fn header(out : &mut dyn Write, elf : &Elf) -> Result<(), Error> {
+    let ehdr = elf.header();
+    let magic : Vec<String> = elf.bytes_at(0, 16)?.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    writeln!(out, "ELF Header:")?;
+    writeln!(out, "  Magic:   {}", magic.join(" "))?;
+    writeln!(out, "  {:<34} {}", "Class:", ehdr.e_ident.class)?;
+    writeln!(out, "  {:<34} {}", "Data:", ehdr.e_ident.endianness)?;
+    writeln!(out, "  {:<34} {}", "Version:", ehdr.e_ident.version)?;
+    writeln!(out, "  {:<34} {}", "OS/ABI:", ehdr.e_ident.osabi)?;
+    writeln!(out, "  {:<34} {}", "ABI Version:", ehdr.e_ident.abi_version)?;
+    writeln!(out, "  {:<34} {}", "Type:", ehdr.e_type)?;
+    writeln!(out, "  {:<34} {}", "Machine:", ehdr.e_machine)?;
+    writeln!(out, "  {:<34} {:?}", "Version:", ehdr.e_version)?;
+    writeln!(out, "  {:<34} 0x{:x}", "Entry point address:", ehdr.e_entry)?;
+    writeln!(out, "  {:<34} {} (bytes into file)", "Start of program headers:",
+             ehdr.e_phoff)?;
+    writeln!(out, "  {:<34} {} (bytes into file)", "Start of section headers:",
+             ehdr.e_shoff)?;
+    writeln!(out, "  {:<34} 0x{:x}", "Flags:", ehdr.e_flags)?;
+    writeln!(out, "  {:<34} {} (bytes)", "Size of this header:",
+             ehdr.e_ehsize)?;
+    writeln!(out, "  {:<34} {} (bytes)", "Size of program headers:",
+             ehdr.e_phentsize)?;
+    writeln!(out, "  {:<34} {}", "Number of program headers:", ehdr.e_phnum)?;
+    writeln!(out, "  {:<34} {} (bytes)", "Size of section headers:",
+             ehdr.e_shentsize)?;
+    writeln!(out, "  {:<34} {}", "Number of section headers:", ehdr.e_shnum)?;
+    writeln!(out, "  {:<34} {}", "Section header string table index:",
+             ehdr.e_shstrndx)?;
+    Ok(())
+}
+
+/// Prints the program header table and the sections each segment holds
+/// (`readelf -l`)
+pub fn segments(out : &mut dyn Write, elf : &Elf) -> Result<(), Error> {
+    let segments = elf.segments();
+    let sections = elf.sections();
+    if segments.is_empty() {
+        writeln!(out, "There are no program headers in this file.")?;
+        return Ok(());
+    }
+
+    writeln!(out, "Elf file type is {}", elf.file_type())?;
+    writeln!(out, "Entry point 0x{:x}", elf.entry())?;
+    writeln!(out, "There are {} program headers, starting at offset {}",
+             segments.len(), elf.phoff())?;
+    writeln!(out)?;
+    writeln!(out, "Program Headers:")?;
+    writeln!(out, "  {:<16} {:<8} {:<18} {:<18} {:<8} {:<8} {:<3} Align",
+             "Type", "Offset", "VirtAddr", "PhysAddr", "FileSiz", "MemSiz",
+             "Flg")?;
+    for phdr in segments {
+        let flags = format!("{}{}{}",
+                            if phdr.has_r() { "R" } else { " " },
+                            if phdr.has_w() { "W" } else { " " },
+                            if phdr.has_x() { "E" } else { " " });
+        writeln!(out, "  {:<16} 0x{:06x} 0x{:016x} 0x{:016x} 0x{:06x} 0x{:06x} \
+                  {:<3} 0x{:x}",
+                 phdr.p_type.to_string(), phdr.p_offset, phdr.p_vaddr,
+                 phdr.p_paddr, phdr.p_filesz, phdr.p_memsz, flags,
+                 phdr.p_align)?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, " Section to Segment mapping:")?;
+    writeln!(out, "  Segment Sections...")?;
+    for (i, phdr) in segments.iter().enumerate() {
+        let names : Vec<&str> = sections.iter()
+            .filter(|shdr| shdr.sh_flags & SHF_ALLOC != 0 && shdr.sh_size != 0)
+            .filter(|shdr| {
+                // Bounds that overflow cannot describe a contained section
+                let end = shdr.sh_addr.checked_add(shdr.sh_size);
+                let limit = phdr.p_vaddr.checked_add(phdr.p_memsz);
+                shdr.sh_addr >= phdr.p_vaddr
+                    && matches!((end, limit), (Some(end), Some(limit))
+                                if end <= limit)
+            })
+            .map(|shdr| shdr.name.as_str())
+            .collect();
+        writeln!(out, "   {:02}     {}", i, names.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Letters describing the `sh_flags` of a section, as `readelf` prints them
+fn section_flags(flags : u64) -> String {
+    [(0x1, 'W'), (0x2, 'A'), (0x4, 'X'), (0x10, 'M'), (0x20, 'S'),
+     (0x40, 'I'), (0x80, 'L'), (0x100, 'O'), (0x200, 'G'), (0x400, 'T'),
+     (0x800, 'C'), (0x80000000, 'E')].iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, letter)| *letter)
+        .collect()
+}
+
+/// Prints the section header table (`readelf -S`)
+pub fn sections(out : &mut dyn Write, elf : &Elf) -> Result<(), Error> {
+    let sections = elf.sections();
+    if sections.is_empty() {
+        writeln!(out, "There are no sections in this file.")?;
+        return Ok(());
+    }
+
+    writeln!(out, "There are {} section headers, starting at offset 0x{:x}:",
+             sections.len(), elf.shoff())?;
+    writeln!(out)?;
+    writeln!(out, "Section Headers:")?;
+    writeln!(out, "  [Nr] {:<20} {:<16} {:<16} {:<8} {:<8} {:<4} {:<3} {:>2} \
+              {:>3} Al",
+             "Name", "Type", "Address", "Off", "Size", "ES", "Flg", "Lk",
+             "Inf")?;
+    for (i, shdr) in sections.iter().enumerate() {
+        writeln!(out, "  [{:>2}] {:<20} {:<16} {:016x} {:08x} {:08x} {:04x} \
+                  {:<3} {:>2} {:>3} {}",
+                 i, shdr.name, shdr.sh_type.to_string(), shdr.sh_addr,
+                 shdr.sh_offset, shdr.sh_size, shdr.sh_entsize,
+                 section_flags(shdr.sh_flags), shdr.sh_link, shdr.sh_info,
+                 shdr.sh_addralign)?;
+    }
+    writeln!(out, "Key to Flags:")?;
+    writeln!(out, "  W (write), A (alloc), X (execute), M (merge), \
+              S (strings), I (info),")?;
+    writeln!(out, "  L (link order), O (extra OS processing required), \
+              G (group), T (TLS),")?;
+    writeln!(out, "  C (compressed), E (exclude)")?;
+    Ok(())
+}
+
+/// Prints the symbol tables (`readelf -s`)
+pub fn symbols(out : &mut dyn Write, elf : &Elf, demangle : bool)
+    -> Result<(), Error>
+{
+    let tables : Vec<&Elf64Shdr> = elf.sections().iter()
+        .filter(|shdr| matches!(shdr.sh_type, SHType::ShtSYMTAB
+                                | SHType::ShtDYNSYM))
+        .collect();
+    if tables.is_empty() {
+        writeln!(out, "There are no symbol tables in this file.")?;
+    }
+
+    for shdr in tables {
+        let symbols = demangle_symbols(elf.symbols_in(shdr)?, demangle);
+        writeln!(out)?;
+        writeln!(out, "Symbol table '{}' contains {} entries:",
+                 shdr.name, symbols.len())?;
+        writeln!(out, "{:>6}: {:<16} {:>5} {:<10} {:<12} {:<12} {:>3} Name",
+                 "Num", "Value", "Size", "Type", "Bind", "Vis", "Ndx")?;
+        for (i, sym) in symbols.iter().enumerate() {
+            let ndx = match sym.shndx {
+                SHN_UNDEF => "UND".to_string(),
+                SHN_ABS => "ABS".to_string(),
+                SHN_COMMON => "COM".to_string(),
+                shndx => shndx.to_string(),
+            };
+            writeln!(out, "{:>6}: {:016x} {:>5} {:<10} {:<12} {:<12} {:>3} {}",
+                     i, sym.value, sym.size, sym.sym_type.to_string(),
+                     sym.bind.to_string(), sym.visibility.to_string(), ndx,
+                     sym.versioned_name())?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the dynamic array (`readelf -d`)
+pub fn dynamic(out : &mut dyn Write, elf : &Elf) -> Result<(), Error> {
+    let dynamic = match elf.dynamic()? {
+        Some(dynamic) => dynamic,
+        None => {
+            writeln!(out, "There is no dynamic section in this file.")?;
+            return Ok(());
+        }
+    };
+
+    writeln!(out, "Dynamic section contains {} entries:",
+             dynamic.entries.len())?;
+    writeln!(out, "  {:<18} {:<20} Name/Value", "Tag", "Type")?;
+    for entry in &dynamic.entries {
+        let value = match &entry.string {
+            Some(string) => string.clone(),
+            None => format!("0x{:x}", entry.value),
+        };
+        writeln!(out, "  0x{:016x} {:<20} {}",
+                 i64::from(entry.tag), entry.tag.to_string(), value)?;
+    }
+    Ok(())
+}
+
+/// Version families whose highest required version is reported, the
+/// runtimes that decide which distributions a binary runs on
+pub const RUNTIME_FAMILIES : [&str; 3] = ["GLIBC", "GLIBCXX", "CXXABI"];
+
+/// Highest version of each runtime family required by the file, with the
+/// symbols requiring them demangled when `demangle` is set
+pub fn runtime_versions(elf : &Elf, demangle : bool)
+    -> Result<Vec<RequiredVersion>, ElfError>
+{
+    Ok(elf.max_required_versions()?.into_iter()
+        .filter(|r| RUNTIME_FAMILIES.contains(&r.family.as_str()))
+        .map(|mut r| {
+            r.symbol = r.symbol.map(|name| symbol_name(&name, demangle));
+            r
+        })
+        .collect())
+}
+
+/// Highest `GLIBC` version required by the file
+pub fn glibc_version(elf : &Elf) -> Result<Option<RequiredVersion>, ElfError> {
+    Ok(elf.max_required_versions()?.into_iter()
+        .find(|r| r.family == "GLIBC"))
+}
+
+/// Prints the versions the file defines and requires (`readelf -V`)
+pub fn versions(out : &mut dyn Write, elf : &Elf, demangle : bool)
+    -> Result<(), Error>
+{
+    let definitions = elf.version_definitions()?;
+    let requirements = elf.version_requirements()?;
+    if definitions.is_empty() && requirements.is_empty() {
+        writeln!(out, "No version information found in this file.")?;
+        return Ok(());
+    }
+
+    if !definitions.is_empty() {
+        writeln!(out, "Version definitions:")?;
+        for def in &definitions {
+            let flags = if def.is_base() { "BASE" } else { "none" };
+            write!(out, "  Index: {:<3} Flags: {:<5} Name: {}",
+                   def.index, flags, def.name)?;
+            if !def.parents.is_empty() {
+                write!(out, " (parents: {})", def.parents.join(", "))?;
+            }
+            writeln!(out)?;
+        }
+    }
+
+    if !requirements.is_empty() {
+        if !definitions.is_empty() {
+            writeln!(out)?;
+        }
+        writeln!(out, "Version requirements:")?;
+        for need in &requirements {
+            writeln!(out, "  File: {}", need.file)?;
+            for version in &need.versions {
+                let flags = if version.is_weak() { "WEAK" } else { "none" };
+                writeln!(out, "    Index: {:<3} Flags: {:<5} Name: {}",
+                         version.index, flags, version.name)?;
+            }
+        }
+    }
+
+    let highest = runtime_versions(elf, demangle)?;
+    if !highest.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Highest versions required:")?;
+        for required in &highest {
+            write!(out, "  {:<20} from {}", required.name, required.file)?;
+            if let Some(symbol) = &required.symbol {
+                write!(out, ", by {}", symbol)?;
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the relocation sections (`readelf -r`)
+pub fn relocs(out : &mut dyn Write, elf : &Elf, demangle : bool)
+    -> Result<(), Error>
+{
+    let sections = elf.relocations()?;
+    if sections.is_empty() {
+        writeln!(out, "There are no relocations in this file.")?;
+    }
+
+    for section in &sections {
+        writeln!(out)?;
+        writeln!(out, "Relocation section '{}' at offset 0x{:x} contains {} \
+                  entries:", section.section.name, section.section.sh_offset,
+                 section.relocs.len())?;
+        writeln!(out, "  {:<16} {:<24} {:>6} Sym. Name + Addend",
+                 "Offset", "Type", "Sym")?;
+        for reloc in &section.relocs {
+            let r_type = elf.reloc_type_name(reloc.r_type)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("<unknown {}>", reloc.r_type));
+            let symbol = reloc.symbol.as_deref()
+                .map(|name| symbol_name(name, demangle));
+            let target = match (&symbol, reloc.addend) {
+                (Some(name), Some(addend)) if addend < 0 =>
+                    format!("{} - {:x}", name, -(addend as i128)),
+                (Some(name), Some(addend)) => format!("{} + {:x}", name, addend),
+                (Some(name), None) => name.clone(),
+                (None, Some(addend)) => format!("{:x}", addend),
+                (None, None) => String::new(),
+            };
+            writeln!(out, "  {:016x} {:<24} {:>6} {}",
+                     reloc.offset, r_type, reloc.sym, target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the notes (`readelf -n`)
+pub fn notes(out : &mut dyn Write, elf : &Elf) -> Result<(), Error> {
+    let notes = elf.notes()?;
+    if notes.is_empty() {
+        writeln!(out, "There are no notes in this file.")?;
+        return Ok(());
+    }
+
+    writeln!(out, "  {:<20} {:<10} Description", "Owner", "Data size")?;
+    for note in &notes {
+        let n_type = match note.name.as_str() {
+            "GNU" => gnu_note_type_name(note.n_type).map(str::to_string),
+            _ => None,
+        }.unwrap_or_else(|| format!("Unknown note type: (0x{:08x})",
+                                    note.n_type));
+        writeln!(out, "  {:<20} 0x{:08x} {}",
+                 note.name, note.desc.len(), n_type)?;
+        if let Some(decoded) = elf.gnu_note(note)? {
+            writeln!(out, "    {}", decoded)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the mitigations the file is built with
+pub fn checksec(out : &mut dyn Write, elf : &Elf) -> Result<(), Error> {
+    let mitigations = SecurityOptions::get_options(elf)?;
+    writeln!(out, "Entry point\t: 0x{:08x}", elf.entry())?;
+    writeln!(out, "Machine\t\t: {}", elf.machine())?;
+    writeln!(out, "Class\t\t: {}", elf.class())?;
+    writeln!(out, "{}", mitigations)?;
+    Ok(())
+}
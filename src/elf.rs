@@ -1,19 +1,53 @@
-use std::fs::File;
-use std::io::SeekFrom;
-use std::io::prelude::*;
+use std::io::Cursor;
 use std::path::Path;
 use std::fmt;
 use colored::*;
+use num_traits::FromPrimitive;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
 
-mod types;
-use types::*;
+pub mod types;
+pub use types::*;
+
+pub mod error;
+pub use error::ElfError;
+
+pub mod bytes;
+pub use bytes::Bytes;
+
+pub mod symbols;
+pub use symbols::Symbol;
+
+pub mod dynamic;
+pub use dynamic::{Dynamic, DynEntry};
+
+pub mod relocs;
+pub use relocs::{Relocation, RelocSection};
+
+pub mod notes;
+pub use notes::{Note, GnuNote, GnuProperty, AbiTag};
+
+pub mod hash;
+pub use hash::{SysvHash, GnuHash};
+
+pub mod versions;
+pub use versions::{VersionDef, VersionNeed, NeededVersion, SymbolVersion,
+                   RequiredVersion};
+
+pub mod symbolize;
+pub use symbolize::{Symbolizer, Location, LocationKind};
+
+#[cfg(feature = "demangle")]
+pub mod demangle;
 
 mod helpers;
+use helpers::{check_entsize, check_table, read_str, read_table, read_u32,
+              read_u64, slice};
 
 
 /// Represents the different mitigations on RELRO
-#[derive(Debug)]
-enum RelRo {
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RelRo {
+    #[default]
     NoRelRo,
     PartialRelRo,
     FullRelRo,
@@ -25,183 +59,513 @@ impl fmt::Display for RelRo {
     }
 }
 
-impl Default for RelRo {
-    fn default() -> Self {
-        RelRo::NoRelRo
+/// Represents how an `ELF` is placed in memory
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Pie {
+    #[default]
+    NoPie,
+    PieExecutable,
+    StaticPie,
+    SharedObject,
+    Relocatable,
+    Core,
+}
+
+impl Pie {
+    /// The file can be loaded at a random address
+    pub fn is_pie(&self) -> bool {
+        matches!(self, Pie::PieExecutable | Pie::StaticPie | Pie::SharedObject)
+    }
+}
+
+impl fmt::Display for Pie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// How serious a weakness is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// A weakness found in an `ELF` beyond the missing mitigations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    // A loadable segment is both writable and executable
+    RwxSegment { index : usize, vaddr : u64 },
+    // No PT_GNU_STACK, the loader falls back to an executable stack
+    MissingGnuStack,
+    // PT_GNU_STACK requests an executable stack
+    ExecutableStack,
+    // Relocations modify the text segment
+    TextRel,
+    // A library search path may be controlled by another user
+    InsecurePath { tag : DynTag, path : String, reason : &'static str },
+}
+
+impl Finding {
+    /// How serious the weakness is
+    pub fn severity(&self) -> Severity {
+        match self {
+            Finding::RwxSegment { .. } => Severity::High,
+            Finding::MissingGnuStack => Severity::High,
+            Finding::ExecutableStack => Severity::High,
+            Finding::TextRel => Severity::Medium,
+            Finding::InsecurePath { reason, .. } if *reason == OUTSIDE_ORIGIN =>
+                Severity::Medium,
+            Finding::InsecurePath { .. } => Severity::High,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Finding::RwxSegment { index, vaddr } => 
+                write!(f, "segment {} at 0x{:x} is writable and executable",
+                       index, vaddr),
+            Finding::MissingGnuStack => 
+                write!(f, "no PT_GNU_STACK nor .note.GNU-stack, the stack is \
+                       executable"),
+            Finding::ExecutableStack => 
+                write!(f, "PT_GNU_STACK requests an executable stack"),
+            Finding::TextRel => 
+                write!(f, "relocations modify the text segment (TEXTREL)"),
+            Finding::InsecurePath { tag, path, reason } => 
+                write!(f, "{} entry \"{}\" is {}", tag, path, reason),
+        }
+    }
+}
+
+/// Findings are serialized with their severity and description rather than
+/// their fields, which keeps the schema the same for every kind
+impl Serialize for Finding {
+    fn serialize<S : Serializer>(&self, serializer : S) 
+        -> Result<S::Ok, S::Error> 
+    {
+        let kind = match self {
+            Finding::RwxSegment { .. } => "RwxSegment",
+            Finding::MissingGnuStack => "MissingGnuStack",
+            Finding::ExecutableStack => "ExecutableStack",
+            Finding::TextRel => "TextRel",
+            Finding::InsecurePath { .. } => "InsecurePath",
+        };
+        let mut state = serializer.serialize_struct("Finding", 3)?;
+        state.serialize_field("kind", kind)?;
+        state.serialize_field("severity", &self.severity())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Directories any user may write to on common systems
+const WORLD_WRITABLE : &[&str] = &["/tmp", "/var/tmp", "/dev/shm"];
+
+/// Reason given for search paths leaving the directory of the object
+const OUTSIDE_ORIGIN : &str = 
+    "outside of $ORIGIN, which depends on where the object is installed";
+
+/// Components of `path` once `.` and `..` are resolved, along with the
+/// number of `..` that climb above its start
+fn normalize(path : &str) -> (Vec<&str>, usize) {
+    let mut components = Vec::new();
+    let mut above = 0;
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => if components.pop().is_none() {
+                above += 1;
+            },
+            _ => components.push(component),
+        }
+    }
+    (components, above)
+}
+
+/// Reason why a `DT_RPATH` or `DT_RUNPATH` entry is unsafe, if it is
+fn insecure_path(path : &str) -> Option<&'static str> {
+    if path.is_empty() {
+        return Some("empty, which searches the current directory");
+    }
+    // $ORIGIN is the directory of the object, which is as safe as the
+    // object itself as long as the path stays below it
+    let origin = path.strip_prefix("$ORIGIN")
+        .or_else(|| path.strip_prefix("${ORIGIN}"));
+    if let Some(rest) = origin {
+        return match normalize(rest) {
+            (_, 0) => None,
+            _ => Some(OUTSIDE_ORIGIN),
+        };
+    }
+    // The path is only judged by its text, the directory it names on the
+    // machine analyzing the file is not the one of the machine running it
+    if !path.starts_with('/') {
+        return Some("relative to the current directory");
+    }
+    let (components, _) = normalize(path);
+    let writable = WORLD_WRITABLE.iter().any(|dir| {
+        let (dir, _) = normalize(dir);
+        components.starts_with(&dir)
+    });
+    match writable {
+        true => Some("in a world-writable directory"),
+        false => None,
     }
 }
 
 /// Describes the security options enabled for an `ELF`
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct SecurityOptions {
     canary : bool,
     nx     : bool,
     relro  : RelRo,
-    pie    : bool,
+    pie    : Pie,
+    ibt    : bool,
+    shstk  : bool,
+    bti    : bool,
+    pac    : bool,
+    // FORTIFY_SOURCE, with the number of fortified calls out of the calls
+    // that could have been fortified
+    fortify     : bool,
+    fortified   : usize,
+    fortifiable : usize,
+    rwx         : bool,
+    textrel     : bool,
+    findings    : Vec<Finding>,
+}
+
+/// Symbols referenced by code built with a stack protector
+const CANARY_SYMBOLS : &[&str] = &[
+    "__stack_chk_fail", "__stack_chk_guard", "__intel_security_cookie",
+];
+
+/// Functions of the C library that have a fortified `__*_chk` counterpart
+const FORTIFIABLE : &[&str] = &[
+    "asprintf", "confstr", "dprintf", "explicit_bzero", "fgets", 
+    "fgets_unlocked", "fgetws", "fgetws_unlocked", "fprintf", "fread", 
+    "fread_unlocked", "fwprintf", "getcwd", "getdomainname", "getgroups", 
+    "gethostname", "getlogin_r", "gets", "getwd", "longjmp", "mbsnrtowcs", 
+    "mbsrtowcs", "mbstowcs", "memcpy", "memmove", "mempcpy", "memset", 
+    "obstack_printf", "obstack_vprintf", "poll", "ppoll", "pread", "pread64", 
+    "printf", "read", "readlink", "readlinkat", "realpath", "recv", 
+    "recvfrom", "snprintf", "sprintf", "stpcpy", "stpncpy", "strcat", 
+    "strcpy", "strlcat", "strlcpy", "strncat", "strncpy", "swprintf", 
+    "syslog", "ttyname_r", "vasprintf", "vdprintf", "vfprintf", "vfwprintf", 
+    "vprintf", "vsnprintf", "vsprintf", "vswprintf", "vsyslog", "vwprintf", 
+    "wcpcpy", "wcpncpy", "wcrtomb", "wcscat", "wcscpy", "wcslcat", "wcslcpy", 
+    "wcsncat", "wcsncpy", "wcsnrtombs", "wcsrtombs", "wcstombs", "wctomb", 
+    "wmemcpy", "wmemmove", "wmempcpy", "wmemset", "wprintf",
+];
+
+/// Colors a mitigation depending on whether it is enabled
+fn colored_bool(enabled : bool) -> ColoredString {
+    match enabled {
+        true => enabled.to_string().green(),
+        false => enabled.to_string().red(),
+    }
+}
+
+/// Colors a weakness depending on whether it is present
+fn colored_flaw(present : bool) -> ColoredString {
+    match present {
+        true => present.to_string().red(),
+        false => present.to_string().green(),
+    }
 }
 
 impl fmt::Display for SecurityOptions {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let canary_colored = match self.canary {
-            true => self.canary.to_string().green(),
-            false => self.canary.to_string().red(),
-        };
-        let nx_colored = match self.nx {
-            true => self.nx.to_string().green(),
-            false => self.nx.to_string().red(),
-        };
         let relro_colored = match self.relro {
             RelRo::NoRelRo => self.relro.to_string().red(),
             RelRo::PartialRelRo => self.relro.to_string().yellow(),
             RelRo::FullRelRo => self.relro.to_string().green(),
         };
         let pie_colored = match self.pie {
-            true => self.pie.to_string().green(),
-            false => self.pie.to_string().red(),
+            Pie::NoPie => self.pie.to_string().red(),
+            Pie::Relocatable | Pie::Core => self.pie.to_string().yellow(),
+            _ => self.pie.to_string().green(),
         };
-        write!(f, "Canary\t\t: {}\nNX\t\t: {}\nRELRO\t\t: {}\nPIE\t\t: {}", 
-               canary_colored,
-               nx_colored,
+        write!(f, "Canary\t\t: {}\nNX\t\t: {}\nRELRO\t\t: {}\nPIE\t\t: {}\n\
+                   IBT\t\t: {}\nSHSTK\t\t: {}\nBTI\t\t: {}\nPAC\t\t: {}\n\
+                   FORTIFY\t\t: {} ({}/{} fortified)\n\
+                   RWX\t\t: {}\nTEXTREL\t\t: {}", 
+               colored_bool(self.canary),
+               colored_bool(self.nx),
                relro_colored,
-               pie_colored)
+               pie_colored,
+               colored_bool(self.ibt),
+               colored_bool(self.shstk),
+               colored_bool(self.bti),
+               colored_bool(self.pac),
+               colored_bool(self.fortify),
+               self.fortified,
+               self.fortifiable,
+               colored_flaw(self.rwx),
+               colored_flaw(self.textrel))?;
+
+        for finding in &self.findings {
+            let severity = match finding.severity() {
+                Severity::High => finding.severity().to_string().red(),
+                Severity::Medium => finding.severity().to_string().yellow(),
+                Severity::Low => finding.severity().to_string().normal(),
+            };
+            write!(f, "\n[{}] {}", severity, finding)?;
+        }
+        Ok(())
     }
 }
 
 impl SecurityOptions {
-    /// Return enabled Security options from an `ELF`
-    pub fn get_options_64(elf : &ELF64, io : &mut std::fs::File) 
-        -> Option<SecurityOptions> {
+    /// A stack protector guards the return addresses
+    pub fn canary(&self) -> bool {
+        self.canary
+    }
 
-        let mut secop = SecurityOptions::default();
-        
-        // Get reference to the section header strtab
-        let shstrtab_section = elf.sht.get(elf.header.e_shstrndx as usize)?;
-        io.seek(SeekFrom::Start(shstrtab_section.sh_offset)).ok()?;
-        let mut buf = vec![0; shstrtab_section.sh_size as usize];
-        io.read_exact(&mut buf).ok()?;
-        let shstrtab = String::from_utf8(buf).expect("Could not read .shstrtab");
-        
-        let index_strtab = shstrtab.find(".strtab").expect("Could not find .strtab");
+    /// The stack is not executable
+    pub fn nx(&self) -> bool {
+        self.nx
+    }
 
-        // Check if canary is present
+    /// How much of the GOT is made read-only after relocation
+    pub fn relro(&self) -> RelRo {
+        self.relro
+    }
 
-        // Get reference to the symbol table
-        let mut iter = elf.sht.iter();
-        let symtab = iter.find(|&x| x.sh_name == index_strtab as u32)
-            .expect("Could not find STRTAB .strtab section");
+    /// How the file is placed in memory
+    pub fn pie(&self) -> Pie {
+        self.pie
+    }
 
-        io.seek(SeekFrom::Start(symtab.sh_offset)).ok()?;
-        
-        // Read strtab
-        let mut buf = vec![0; symtab.sh_size as usize];
-        io.read_exact(&mut buf).expect("Error reading string table");
-        
-        // Check if strtab contains __stack_chk_fail
-        let symbols = String::from_utf8(buf).expect("Could not read .symtab");
-        if symbols.contains("__stack_chk_fail") {
-            secop.canary = true;
-        }
+    /// Indirect branch tracking (Intel CET)
+    pub fn ibt(&self) -> bool {
+        self.ibt
+    }
 
-        // Check if NX is present 
-        
-        // Get reference to gnu_stack 
-        let mut iter = elf.pht.iter();
-        let gnu_stack = iter.find(|&x| x.p_type == PType::PtGnuStack)
-            .expect("Could not find gnu_stack segment");
+    /// Shadow stack (Intel CET)
+    pub fn shstk(&self) -> bool {
+        self.shstk
+    }
 
-        secop.nx = !gnu_stack.has_x();
+    /// Branch target identification (AArch64)
+    pub fn bti(&self) -> bool {
+        self.bti
+    }
 
+    /// Pointer authentication (AArch64)
+    pub fn pac(&self) -> bool {
+        self.pac
+    }
 
-        // Check RELRO level
+    /// Some calls go through the fortified `__*_chk` functions
+    pub fn fortify(&self) -> bool {
+        self.fortify
+    }
 
-        // Get reference to GNU RELRO
-        let mut iter = elf.pht.iter();
-        let gnu_relro = iter.find(|&x| x.p_type == PType::PtGnuRelro);
+    /// Number of calls that go through a fortified function
+    pub fn fortified(&self) -> usize {
+        self.fortified
+    }
 
-        if gnu_relro.is_some() {
-            if !shstrtab.contains(".got.plt") { secop.relro = RelRo::FullRelRo; }
-            else { secop.relro = RelRo::PartialRelRo; }
-        } else { secop.relro = RelRo::NoRelRo; }
-        
-        // Check if PIE is present
-        // If the binary is a shared object (of type EtDyn), PIE
-        // If the binary is of type EtExec, no PIE
+    /// Number of calls that could go through a fortified function
+    pub fn fortifiable(&self) -> usize {
+        self.fortifiable
+    }
 
-        secop.pie = match elf.header.e_type {
-            EType::EtDyn => true,
-            EType::EtExec => false,
-            _ => unimplemented!(),
-        };
+    /// A loadable segment is both writable and executable
+    pub fn rwx(&self) -> bool {
+        self.rwx
+    }
+
+    /// Relocations modify the text segment
+    pub fn textrel(&self) -> bool {
+        self.textrel
+    }
 
-        Some(secop)
+    /// Weaknesses found in the file, beyond the missing mitigations
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
     }
 
     /// Return enabled Security options from an `ELF`
-    pub fn get_options_32(elf : &ELF32, io : &mut std::fs::File) 
-        -> Option<SecurityOptions> {
+    pub fn get_options(elf : &Elf) -> Result<SecurityOptions, ElfError> {
 
         let mut secop = SecurityOptions::default();
+        let segments = elf.segments();
+        let dynamic = elf.dynamic()?;
         
-        // Get reference to the section header strtab
-        let shstrtab_section = elf.sht.get(elf.header.e_shstrndx as usize)?;
-        io.seek(SeekFrom::Start(shstrtab_section.sh_offset as u64)).ok()?;
-        let mut buf = vec![0; shstrtab_section.sh_size as usize];
-        io.read_exact(&mut buf).ok()?;
-        let shstrtab = String::from_utf8(buf).expect("Could not read .shstrtab");
-        
-        let index_strtab = shstrtab.find(".strtab").expect("Could not find .strtab");
-
         // Check if canary is present
 
-        // Get reference to the symbol table
-        let mut iter = elf.sht.iter();
-        let symtab = iter.find(|&x| x.sh_name == index_strtab as u32)
-            .expect("Could not find STRTAB .strtab section");
+        // Look for the symbols of the stack protector in both symbol tables,
+        // stripped files only keep the dynamic one and files without section
+        // headers have none, in which case no canary is reported
+        let mut symbols = elf.symbols()?;
+        symbols.extend(elf.dynamic_symbols()?);
+        secop.canary = symbols.iter()
+            .any(|sym| CANARY_SYMBOLS.contains(&sym.name.as_str()));
 
-        io.seek(SeekFrom::Start(symtab.sh_offset as u64)).ok()?;
-        
-        // Read strtab
-        let mut buf = vec![0; symtab.sh_size as usize];
-        io.read_exact(&mut buf).expect("Error reading string table");
+        // Check if NX is present 
         
-        // Check if strtab contains __stack_chk_fail
-        let symbols = String::from_utf8(buf).expect("Could not read .symtab");
-        if symbols.contains("__stack_chk_fail") {
-            secop.canary = true;
+        // Relocatables record the stack each object needs in the flags of 
+        // .note.GNU-stack, other files in PT_GNU_STACK. Without either the
+        // loader maps an executable stack.
+        let exec_stack = match elf.file_type() {
+            EType::EtRel => elf.section_by_name(".note.GNU-stack")
+                .map(|shdr| shdr.sh_flags & SHF_EXECINSTR != 0),
+            _ => segments.iter()
+                .find(|&x| x.p_type == PType::PtGnuStack)
+                .map(|gnu_stack| gnu_stack.has_x()),
+        };
+
+        secop.nx = exec_stack == Some(false);
+        match exec_stack {
+            None => secop.findings.push(Finding::MissingGnuStack),
+            Some(true) => secop.findings.push(Finding::ExecutableStack),
+            Some(false) => {}
         }
 
-        // Check if NX is present 
-        
-        // Get reference to gnu_stack 
-        let mut iter = elf.pht.iter();
-        let gnu_stack = iter.find(|&x| x.p_type == PType::PtGnuStack)
-            .expect("Could not find gnu_stack segment");
+        // Check for segments both writable and executable
+
+        for (index, phdr) in segments.iter().enumerate() {
+            if phdr.p_type == PType::PtLoad && phdr.has_w() && phdr.has_x() {
+                secop.rwx = true;
+                secop.findings.push(Finding::RwxSegment { 
+                    index, 
+                    vaddr : phdr.p_vaddr,
+                });
+            }
+        }
 
-        secop.nx = !gnu_stack.has_x();
+        // Check for relocations in the text segment and library search 
+        // paths that could be hijacked
 
+        if let Some(dynamic) = &dynamic {
+            secop.textrel = dynamic.get(DynTag::DtTextRel).is_some()
+                || dynamic.flags() & DF_TEXTREL != 0;
+            if secop.textrel {
+                secop.findings.push(Finding::TextRel);
+            }
+
+            for entry in &dynamic.entries {
+                if !matches!(entry.tag, DynTag::DtRpath | DynTag::DtRunpath) {
+                    continue;
+                }
+                let paths = entry.string.as_deref().unwrap_or_default();
+                for path in paths.split(':') {
+                    if let Some(reason) = insecure_path(path) {
+                        secop.findings.push(Finding::InsecurePath {
+                            tag    : entry.tag,
+                            path   : path.to_string(),
+                            reason,
+                        });
+                    }
+                }
+            }
+        }
 
         // Check RELRO level
 
         // Get reference to GNU RELRO
-        let mut iter = elf.pht.iter();
+        let mut iter = segments.iter();
         let gnu_relro = iter.find(|&x| x.p_type == PType::PtGnuRelro);
 
-        if gnu_relro.is_some() {
-            if !shstrtab.contains(".got.plt") { secop.relro = RelRo::FullRelRo; }
-            else { secop.relro = RelRo::PartialRelRo; }
-        } else { secop.relro = RelRo::NoRelRo; }
+        // The GOT is only covered entirely when the loader resolves every 
+        // symbol at startup, which it does when asked to bind now. Files 
+        // without a dynamic array have nothing to bind lazily.
+        let bind_now = match &dynamic {
+            Some(dynamic) => dynamic.get(DynTag::DtBindNow).is_some()
+                || dynamic.flags() & DF_BIND_NOW != 0
+                || dynamic.flags_1() & DF_1_NOW != 0,
+            None => true,
+        };
+
+        secop.relro = match (gnu_relro, bind_now) {
+            (None, _) => RelRo::NoRelRo,
+            (Some(_), false) => RelRo::PartialRelRo,
+            (Some(_), true) => RelRo::FullRelRo,
+        };
         
         // Check if PIE is present
-        // If the binary is a shared object (of type EtDyn), PIE
-        // If the binary is of type EtExec, no PIE
+        // Executables of type EtDyn are marked with DF_1_PIE by recent 
+        // linkers or request an interpreter, static-pie ones do not have one.
+        // Libraries such as libc.so.6 also request an interpreter so that
+        // they can be run, unlike executables they have a DT_SONAME.
 
-        secop.pie = match elf.header.e_type {
-            EType::EtDyn => true,
-            EType::EtExec => false,
-            _ => unimplemented!(),
+        let pie_flag = dynamic.as_ref()
+            .is_some_and(|dynamic| dynamic.flags_1() & DF_1_PIE != 0);
+        let soname = dynamic.as_ref()
+            .is_some_and(|dynamic| dynamic.soname().is_some());
+        let interp = segments.iter().any(|x| x.p_type == PType::PtInterp);
+
+        secop.pie = match elf.file_type() {
+            EType::EtDyn if pie_flag && !interp => Pie::StaticPie,
+            EType::EtDyn if pie_flag => Pie::PieExecutable,
+            EType::EtDyn if interp && !soname => Pie::PieExecutable,
+            EType::EtDyn => Pie::SharedObject,
+            EType::EtExec => Pie::NoPie,
+            EType::EtRel => Pie::Relocatable,
+            EType::EtCore => Pie::Core,
+            e_type => return Err(ElfError::InvalidValue {
+                offset : 0x10,
+                field  : "e_type",
+                value  : u16::from(*e_type) as u64,
+            }),
         };
 
-        Some(secop)
+        // Check control-flow protection, which the linker only marks when
+        // every input object was built with it
+
+        for property in elf.program_properties()? {
+            match property {
+                GnuProperty::X86Feature1And(features) => {
+                    secop.ibt = features & GNU_PROPERTY_X86_FEATURE_1_IBT != 0;
+                    secop.shstk = 
+                        features & GNU_PROPERTY_X86_FEATURE_1_SHSTK != 0;
+                }
+                GnuProperty::AArch64Feature1And(features) => {
+                    secop.bti = 
+                        features & GNU_PROPERTY_AARCH64_FEATURE_1_BTI != 0;
+                    secop.pac = 
+                        features & GNU_PROPERTY_AARCH64_FEATURE_1_PAC != 0;
+                }
+                _ => {}
+            }
+        }
+
+        // Check FORTIFY_SOURCE, comparing the fortified functions imported
+        // against the ones that are imported unfortified
+
+        let imports = elf.imports()?;
+        // Only the `__*_chk` functions checking a fortifiable function count,
+        // others such as `__fdelt_chk` have no unfortified counterpart
+        let mut fortified : Vec<&str> = imports.iter()
+            .filter_map(|sym| sym.name.strip_prefix("__")?.strip_suffix("_chk"))
+            .filter(|name| FORTIFIABLE.contains(name))
+            .collect();
+        let mut unfortified : Vec<&str> = imports.iter()
+            .map(|sym| sym.name.as_str())
+            .filter(|name| FORTIFIABLE.contains(name) 
+                    && !fortified.contains(name))
+            .collect();
+        fortified.sort_unstable();
+        fortified.dedup();
+        unfortified.sort_unstable();
+        unfortified.dedup();
+
+        secop.fortify = !fortified.is_empty();
+        secop.fortified = fortified.len();
+        secop.fortifiable = fortified.len() + unfortified.len();
+
+        Ok(secop)
     }
 }
 
@@ -234,56 +598,96 @@ impl Default for ELF {
 /// Macro that setups the functions and structs for 64 and 32 bits
 /// architectures
 macro_rules! setup_arch {
-    ($name:ident, $header_type:ty, $ph_type:ty, $sh_type:ty) => {
+    ($name:ident, $header_type:ty, $ph_type:ty, $sh_type:ty, 
+     $sym_type:ty, $dyn_type:ty, $rel_type:ty, $rela_type:ty, 
+     $word_type:ty) => {
         
         /// Represents an ELF executable
-        pub struct $name {
+        pub struct $name<'a> {
             // Header for the file
             pub header   : $header_type,
             // Program header table
             pub pht      : Vec<$ph_type>,
             // Section header table
             pub sht      : Vec<$sh_type>,
-            // Security options enabled for the ELF
-            pub mitigations : SecurityOptions,
+            // Both tables widened to the 64 bits layout once, at parse time
+            segments     : Vec<Elf64Phdr>,
+            sections     : Vec<Elf64Shdr>,
+            // Content of the file
+            data         : Bytes<'a>,
         }
 
         /// Impl default method to initialize an `ELF` object
-        impl Default for $name {
+        impl Default for $name<'_> {
             fn default() -> Self { 
                 $name {
                     header      : <$header_type>::default(),
                     pht         : Vec::new(),
                     sht         : Vec::new(),
-                    mitigations : SecurityOptions::default(),
+                    segments    : Vec::new(),
+                    sections    : Vec::new(),
+                    data        : Bytes::default(),
                 } 
             }
         }
 
-        impl $name {
+        impl $name<'static> {
             /// Loads an `ELF` file from a `Path`
-            pub fn load<P : AsRef<Path>>(path_to_file : P) -> Option<$name> {
+            pub fn load<P : AsRef<Path>>(path_to_file : P) 
+                -> Result<$name<'static>, ElfError> 
+            {
+                $name::from_bytes(Bytes::read(path_to_file)?)
+            }
+
+            /// Loads an `ELF` file from a `Path` by mapping it in memory
+            #[cfg(feature = "mmap")]
+            pub fn map<P : AsRef<Path>>(path_to_file : P) 
+                -> Result<$name<'static>, ElfError> 
+            {
+                $name::from_bytes(Bytes::map(path_to_file)?)
+            }
+        }
+
+        impl<'a> $name<'a> {
+            /// Parses an `ELF` file held in memory, borrowing its content
+            pub fn parse(data : &'a [u8]) -> Result<$name<'a>, ElfError> {
+                $name::from_bytes(Bytes::Borrowed(data))
+            }
+
+            /// Parses an `ELF` file from its content
+            pub fn from_bytes(data : Bytes<'a>) -> Result<$name<'a>, ElfError> {
                 let mut elf = $name::default();
 
-                let mut file = File::open(path_to_file).expect("File not found");
+                let len = data.len() as u64;
+                let mut file = Cursor::new(&data[..]);
             
                 // Parse Header 
 
-                elf.header = <$header_type>::from_io(&mut file)
-                    .expect("Header parsing error");
+                elf.header = <$header_type>::from_io(&mut file)?;
+                let endian = elf.header.e_ident.endianness;
 
                 // ======================== Parse Program Header Table
                 let mut proght : Vec<$ph_type> = 
                     Vec::with_capacity(elf.header.e_phnum as usize);
 
-                // Set reader cursor to the position of the section header table
-                // in the file
-                file.seek(SeekFrom::Start(elf.header.e_phoff as u64))
-                    .expect("Cannot set cursor to pht offset");
+                check_table(elf.header.e_phoff as u64, 
+                            elf.header.e_phnum as u64,
+                            elf.header.e_phentsize as u64, len)?;
+                // Smaller entries would overlap each other
+                if elf.header.e_phnum != 0 {
+                    check_entsize(elf.header.e_phoff as u64,
+                                  elf.header.e_phentsize as u64,
+                                  <$ph_type>::SIZE, "e_phentsize")?;
+                }
 
                 // Push all pht entries in the pht
-                for _ in 0..elf.header.e_phnum {
-                    let phtentry = <$ph_type>::from_io(&mut file).unwrap();
+                for i in 0..elf.header.e_phnum as u64 {
+                    // Set reader cursor to the position of the entry in the 
+                    // file
+                    let offset = elf.header.e_phoff as u64 
+                        + i * elf.header.e_phentsize as u64;
+                    file.set_position(offset);
+                    let phtentry = <$ph_type>::from_io(&mut file, endian)?;
                     proght.push(phtentry);
                 }
 
@@ -291,42 +695,385 @@ macro_rules! setup_arch {
                 let mut secht : Vec<$sh_type> = 
                     Vec::with_capacity(elf.header.e_shnum as usize);
 
-                // Set reader cursor to the position of the section header table
-                // in the file
-                file.seek(SeekFrom::Start(elf.header.e_shoff as u64))
-                    .expect("Cannot set cursor to sht offset");
+                check_table(elf.header.e_shoff as u64, 
+                            elf.header.e_shnum as u64,
+                            elf.header.e_shentsize as u64, len)?;
+                if elf.header.e_shnum != 0 {
+                    check_entsize(elf.header.e_shoff as u64,
+                                  elf.header.e_shentsize as u64,
+                                  <$sh_type>::SIZE, "e_shentsize")?;
+                }
 
                 // Push all sht entries in the sht
-                for _ in 0..elf.header.e_shnum {
-                    let shtentry = <$sh_type>::from_io(&mut file).unwrap();
+                for i in 0..elf.header.e_shnum as u64 {
+                    // Set reader cursor to the position of the entry in the 
+                    // file
+                    let offset = elf.header.e_shoff as u64 
+                        + i * elf.header.e_shentsize as u64;
+                    file.set_position(offset);
+                    let shtentry = <$sh_type>::from_io(&mut file, endian)?;
                     secht.push(shtentry);
                 }
 
+                // Resolve section names through the section header string 
+                // table, whose index is stored in the first entry when it 
+                // does not fit in e_shstrndx. Names that cannot be read are
+                // left empty, the sections themselves are still usable.
+                let shstrndx = match elf.header.e_shstrndx {
+                    SHN_XINDEX => secht.first()
+                        .map_or(0, |shdr| shdr.sh_link as usize),
+                    index => index as usize,
+                };
+                let names = secht.get(shstrndx)
+                    .filter(|_| shstrndx != SHN_UNDEF as usize)
+                    .and_then(|shstrtab| slice(&data, shstrtab.sh_offset as u64,
+                                               shstrtab.sh_size as u64).ok());
+                if let Some(names) = names {
+                    for shdr in secht.iter_mut() {
+                        shdr.name = read_str(names, shdr.sh_name as u64)
+                            .unwrap_or_default();
+                    }
+                }
+
+                elf.segments = proght.iter().cloned()
+                    .map(Elf64Phdr::from).collect();
+                elf.sections = secht.iter().cloned()
+                    .map(Elf64Shdr::from).collect();
                 elf.pht = proght;
                 elf.sht = secht;
+                elf.data = data;
+
+                Ok(elf)
+            }
+
+            /// Content of the whole file
+            pub fn data(&self) -> &[u8] {
+                &self.data
+            }
+
+            /// Program header table, widened to the 64 bits layout
+            pub fn segments(&self) -> &[Elf64Phdr] {
+                &self.segments
+            }
+
+            /// Section header table, widened to the 64 bits layout
+            pub fn sections(&self) -> &[Elf64Shdr] {
+                &self.sections
+            }
+
+            /// Entries of the symbol table described by `shdr`, widened to 
+            /// the 64 bits layout
+            pub fn symbol_entries(&self, shdr : &Elf64Shdr) 
+                -> Result<Vec<Elf64Sym>, ElfError> 
+            {
+                let endian = self.header.e_ident.endianness;
+                let entsize = match shdr.sh_entsize {
+                    0 => <$sym_type>::SIZE,
+                    n => n,
+                };
+                check_entsize(shdr.sh_offset, entsize, <$sym_type>::SIZE,
+                              "sh_entsize")?;
+                read_table(self.data(), shdr.sh_offset, shdr.sh_size, entsize,
+                           |io| <$sym_type>::from_io(io, endian)
+                                .map(Elf64Sym::from))
+            }
+
+            /// Entry `index` of the symbol table described by `shdr`, 
+            /// widened to the 64 bits layout
+            pub fn symbol_entry(&self, shdr : &Elf64Shdr, index : u64) 
+                -> Result<Elf64Sym, ElfError> 
+            {
+                let endian = self.header.e_ident.endianness;
+                let entsize = match shdr.sh_entsize {
+                    0 => <$sym_type>::SIZE,
+                    n => n,
+                };
+                check_entsize(shdr.sh_offset, entsize, <$sym_type>::SIZE,
+                              "sh_entsize")?;
+                let offset = index.saturating_mul(entsize)
+                    .saturating_add(shdr.sh_offset);
+                if index >= shdr.sh_size / entsize {
+                    return Err(ElfError::OutOfRange { offset, size : entsize });
+                }
+                let mut io = Cursor::new(slice(self.data(), offset, entsize)?);
+                <$sym_type>::from_io(&mut io, endian).map(Elf64Sym::from)
+            }
+
+            /// Entries of the dynamic array located at `offset`, widened to 
+            /// the 64 bits layout
+            pub fn dynamic_entries(&self, offset : u64, size : u64) 
+                -> Result<Vec<Elf64Dyn>, ElfError> 
+            {
+                let endian = self.header.e_ident.endianness;
+                read_table(self.data(), offset, size, <$dyn_type>::SIZE,
+                           |io| <$dyn_type>::from_io(io, endian)
+                                .map(Elf64Dyn::from))
+            }
 
-                //elf.mitigations = SecurityOptions::get_options(&elf, &mut file)
-                //    .expect("Error detecting mitigations");
+            /// Entries of the `SHT_REL` table located at `offset`
+            pub fn rel_entries(&self, offset : u64, size : u64) 
+                -> Result<Vec<Relocation>, ElfError> 
+            {
+                let endian = self.header.e_ident.endianness;
+                read_table(self.data(), offset, size, <$rel_type>::SIZE,
+                           |io| <$rel_type>::from_io(io, endian)
+                                .map(Relocation::from))
+            }
 
-                Some(elf)
+            /// Entries of the `SHT_RELA` table located at `offset`
+            pub fn rela_entries(&self, offset : u64, size : u64) 
+                -> Result<Vec<Relocation>, ElfError> 
+            {
+                let endian = self.header.e_ident.endianness;
+                read_table(self.data(), offset, size, <$rela_type>::SIZE,
+                           |io| <$rela_type>::from_io(io, endian)
+                                .map(Relocation::from))
+            }
+
+            /// Array of address sized words located at `offset`, such as the
+            /// entries of a `SHT_RELR` table
+            pub fn words(&self, offset : u64, size : u64) 
+                -> Result<Vec<u64>, ElfError> 
+            {
+                let endian = self.header.e_ident.endianness;
+                let entsize = std::mem::size_of::<$word_type>() as u64;
+                if entsize == 8 {
+                    read_table(self.data(), offset, size, entsize,
+                               |io| read_u64(io, endian))
+                } else {
+                    read_table(self.data(), offset, size, entsize,
+                               |io| read_u32(io, endian).map(u64::from))
+                }
             }
         }
     }
 }
 
-setup_arch!(ELF64, Elf64Ehdr, Elf64Phdr, Elf64Shdr);
-setup_arch!(ELF32, Elf32Ehdr, Elf32Phdr, Elf32Shdr);
-/*
-pub fn load_elf<P: AsRef<Path>, T: ELF>(path_to_file : P) -> Option<T> {
-    let mut file = File::open(path_to_file).expect("File not found");
-    let mut buf = [0; 5];
-    match buf[4] {
-        1u8 => return Some(ELF64::load(path_to_file).unwrap()),
-        2u8 => return Some(ELF32::load(path_to_file).unwrap()),
-        _ => unimplemented!(),
+setup_arch!(ELF64, Elf64Ehdr, Elf64Phdr, Elf64Shdr, Elf64Sym, Elf64Dyn,
+            Elf64Rel, Elf64Rela, u64);
+setup_arch!(ELF32, Elf32Ehdr, Elf32Phdr, Elf32Shdr, Elf32Sym, Elf32Dyn,
+            Elf32Rel, Elf32Rela, u32);
+
+/// Forwards an expression to the `ELF32` or `ELF64` held by an `Elf`
+macro_rules! dispatch {
+    ($elf:expr, $inner:ident => $body:expr) => {
+        match $elf {
+            Elf::Elf32($inner) => $body,
+            // Widening casts written for the 32 bits fields are no-ops here
+            #[allow(clippy::unnecessary_cast)]
+            Elf::Elf64($inner) => $body,
+        }
     }
 }
-*/
+
+/// An `ELF` file of either class
+///
+/// The accessors return class independent values so that callers never have
+/// to branch on `EiClass`.
+pub enum Elf<'a> {
+    Elf32(ELF32<'a>),
+    Elf64(ELF64<'a>),
+}
+
+impl Elf<'static> {
+    /// Loads an `ELF` file from a `Path`, detecting its class
+    pub fn load<P : AsRef<Path>>(path_to_file : P) 
+        -> Result<Elf<'static>, ElfError> 
+    {
+        Elf::from_bytes(Bytes::read(path_to_file)?)
+    }
+
+    /// Loads an `ELF` file from a `Path` by mapping it in memory, detecting
+    /// its class
+    #[cfg(feature = "mmap")]
+    pub fn map<P : AsRef<Path>>(path_to_file : P) 
+        -> Result<Elf<'static>, ElfError> 
+    {
+        Elf::from_bytes(Bytes::map(path_to_file)?)
+    }
+}
+
+impl<'a> Elf<'a> {
+    /// Parses an `ELF` file held in memory, detecting its class. Section data
+    /// is borrowed from `data` rather than copied.
+    pub fn parse(data : &'a [u8]) -> Result<Elf<'a>, ElfError> {
+        Elf::from_bytes(Bytes::Borrowed(data))
+    }
+
+    /// Parses an `ELF` file from its content, detecting its class
+    pub fn from_bytes(data : Bytes<'a>) -> Result<Elf<'a>, ElfError> {
+        let ident = data.get(0..5)
+            .ok_or(ElfError::Truncated { offset : 0 })?;
+
+        if ident[0..4] != [0x7f, 0x45, 0x4c, 0x46] {
+            return Err(ElfError::BadMagic {
+                offset : 0,
+                magic  : [ident[0], ident[1], ident[2], ident[3]],
+            });
+        }
+        match EiClass::from_u8(ident[4]) {
+            Some(EiClass::ElfClass32) => 
+                Ok(Elf::Elf32(ELF32::from_bytes(data)?)),
+            Some(EiClass::ElfClass64) => 
+                Ok(Elf::Elf64(ELF64::from_bytes(data)?)),
+            _ => Err(ElfError::UnsupportedClass { offset : 4, class : ident[4] }),
+        }
+    }
+
+    /// Content of the whole file
+    pub fn data(&self) -> &[u8] {
+        dispatch!(self, elf => elf.data())
+    }
+
+    /// Borrows the `size` bytes located at `offset` in the file
+    pub fn bytes_at(&self, offset : u64, size : u64) 
+        -> Result<&[u8], ElfError> 
+    {
+        slice(self.data(), offset, size)
+    }
+
+    /// Borrows the content of a section. Sections that do not occupy space in
+    /// the file (`SHT_NOBITS`) have no content.
+    pub fn section_data(&self, shdr : &Elf64Shdr) -> Result<&[u8], ElfError> {
+        if shdr.sh_type == SHType::ShtNOBITS {
+            return Ok(&[]);
+        }
+        self.bytes_at(shdr.sh_offset, shdr.sh_size)
+    }
+
+    /// First section named `name`
+    pub fn section_by_name(&self, name : &str) -> Option<&Elf64Shdr> {
+        self.sections().iter().find(|shdr| shdr.name == name)
+    }
+
+    /// Borrows the content of a segment as stored in the file
+    pub fn segment_data(&self, phdr : &Elf64Phdr) -> Result<&[u8], ElfError> {
+        self.bytes_at(phdr.p_offset, phdr.p_filesz)
+    }
+
+    /// File header, widened to the 64 bits layout
+    pub fn header(&self) -> Elf64Ehdr {
+        match self {
+            Elf::Elf32(elf) => elf.header.clone().into(),
+            Elf::Elf64(elf) => elf.header.clone(),
+        }
+    }
+
+    /// Identification bytes of the file
+    pub fn ident(&self) -> &EIdentStruct {
+        dispatch!(self, elf => &elf.header.e_ident)
+    }
+
+    /// Class of the file
+    pub fn class(&self) -> &EiClass {
+        &self.ident().class
+    }
+
+    /// Byte order of the file
+    pub fn endianness(&self) -> EiData {
+        self.ident().endianness
+    }
+
+    /// Type of object file
+    pub fn file_type(&self) -> &EType {
+        dispatch!(self, elf => &elf.header.e_type)
+    }
+
+    /// Architecture required by the file
+    pub fn machine(&self) -> &EMachine {
+        dispatch!(self, elf => &elf.header.e_machine)
+    }
+
+    /// Entry point of the program
+    pub fn entry(&self) -> u64 {
+        dispatch!(self, elf => elf.header.e_entry as u64)
+    }
+
+    /// Offset of the program header table
+    pub fn phoff(&self) -> u64 {
+        dispatch!(self, elf => elf.header.e_phoff as u64)
+    }
+
+    /// Offset of the section header table
+    pub fn shoff(&self) -> u64 {
+        dispatch!(self, elf => elf.header.e_shoff as u64)
+    }
+
+    /// Index of the section name string table
+    pub fn shstrndx(&self) -> u16 {
+        dispatch!(self, elf => elf.header.e_shstrndx)
+    }
+
+    /// Program header table, widened to the 64 bits layout
+    pub fn segments(&self) -> &[Elf64Phdr] {
+        dispatch!(self, elf => elf.segments())
+    }
+
+    /// Section header table, widened to the 64 bits layout
+    pub fn sections(&self) -> &[Elf64Shdr] {
+        dispatch!(self, elf => elf.sections())
+    }
+
+    /// Entries of the symbol table described by `shdr`, widened to the 64 
+    /// bits layout
+    pub fn symbol_entries(&self, shdr : &Elf64Shdr) 
+        -> Result<Vec<Elf64Sym>, ElfError> 
+    {
+        dispatch!(self, elf => elf.symbol_entries(shdr))
+    }
+
+    /// Entry `index` of the symbol table described by `shdr`, widened to 
+    /// the 64 bits layout
+    pub fn symbol_entry(&self, shdr : &Elf64Shdr, index : u64) 
+        -> Result<Elf64Sym, ElfError> 
+    {
+        dispatch!(self, elf => elf.symbol_entry(shdr, index))
+    }
+
+    /// Entries of the dynamic array located at `offset`, widened to the 64 
+    /// bits layout
+    pub fn dynamic_entries(&self, offset : u64, size : u64) 
+        -> Result<Vec<Elf64Dyn>, ElfError> 
+    {
+        dispatch!(self, elf => elf.dynamic_entries(offset, size))
+    }
+
+    /// Entries of the `SHT_REL` table located at `offset`
+    pub fn rel_entries(&self, offset : u64, size : u64) 
+        -> Result<Vec<Relocation>, ElfError> 
+    {
+        dispatch!(self, elf => elf.rel_entries(offset, size))
+    }
+
+    /// Entries of the `SHT_RELA` table located at `offset`
+    pub fn rela_entries(&self, offset : u64, size : u64) 
+        -> Result<Vec<Relocation>, ElfError> 
+    {
+        dispatch!(self, elf => elf.rela_entries(offset, size))
+    }
+
+    /// Array of address sized words located at `offset`
+    pub fn words(&self, offset : u64, size : u64) 
+        -> Result<Vec<u64>, ElfError> 
+    {
+        dispatch!(self, elf => elf.words(offset, size))
+    }
+
+    /// File offset of the byte mapped at the virtual address `vaddr`, found
+    /// through the `PT_LOAD` segments. Segments whose content lies outside
+    /// of the file are ignored.
+    pub fn vaddr_to_offset(&self, vaddr : u64) -> Option<u64> {
+        let file_size = self.data().len() as u64;
+        self.segments().iter()
+            .filter(|phdr| phdr.p_type == PType::PtLoad)
+            .filter(|phdr| phdr.p_offset.checked_add(phdr.p_filesz)
+                    .is_some_and(|end| end <= file_size))
+            .find(|phdr| vaddr >= phdr.p_vaddr 
+                  && vaddr - phdr.p_vaddr < phdr.p_filesz)
+            .and_then(|phdr| phdr.p_offset.checked_add(vaddr - phdr.p_vaddr))
+    }
+}
+
 /*
 impl ELF {
     /// Loads an `ELF` file from a `Path`
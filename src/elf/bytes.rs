@@ -0,0 +1,56 @@
+use std::ops::Deref;
+use std::path::Path;
+
+use crate::elf::error::ElfError;
+
+/// Content of an `ELF` file
+///
+/// The parsers only ever need a byte slice, this type lets an `ELF` either
+/// borrow the caller's buffer, own a copy read from disk, or keep a memory
+/// mapping of the file alive for as long as it is used.
+pub enum Bytes<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Bytes<'static> {
+    /// Reads the whole file located at `path` in memory
+    pub fn read<P : AsRef<Path>>(path : P) -> Result<Bytes<'static>, ElfError> {
+        std::fs::read(path)
+            .map(Bytes::Owned)
+            .map_err(|source| ElfError::Io { offset : 0, source })
+    }
+
+    /// Maps the file located at `path` in memory
+    #[cfg(feature = "mmap")]
+    pub fn map<P : AsRef<Path>>(path : P) -> Result<Bytes<'static>, ElfError> {
+        let file = std::fs::File::open(path)
+            .map_err(|source| ElfError::Io { offset : 0, source })?;
+        // The mapping is read-only, modifying the file while it is mapped is
+        // the caller's responsibility, as with any other mmap based reader
+        let map = unsafe { memmap2::Mmap::map(&file) }
+            .map_err(|source| ElfError::Io { offset : 0, source })?;
+        Ok(Bytes::Mapped(map))
+    }
+}
+
+impl Deref for Bytes<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Bytes::Borrowed(data) => data,
+            Bytes::Owned(data) => data,
+            #[cfg(feature = "mmap")]
+            Bytes::Mapped(data) => data,
+        }
+    }
+}
+
+impl Default for Bytes<'_> {
+    fn default() -> Self {
+        Bytes::Borrowed(&[])
+    }
+}
@@ -0,0 +1,27 @@
+/// Demangled form of an Itanium C++ or a Rust (legacy or v0) symbol name,
+/// `None` when `name` is not mangled
+pub fn demangle(name : &str) -> Option<String> {
+    // Legacy Rust names are also valid C++ names, but the C++ demangler
+    // would keep their hash
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        // The alternate form leaves out the hash
+        return Some(format!("{:#}", demangled));
+    }
+    cpp_demangle::Symbol::new(name).ok()?
+        .demangle(&cpp_demangle::DemangleOptions::default()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demangle_names() {
+        assert_eq!(demangle("_Z3fooi").as_deref(), Some("foo(int)"));
+        // The hash of legacy Rust names is left out
+        let legacy = "_ZN4core3fmt5write17h0123456789abcdefE";
+        assert_eq!(demangle(legacy).as_deref(), Some("core::fmt::write"));
+        assert_eq!(demangle("printf"), None);
+        assert_eq!(demangle(""), None);
+    }
+}
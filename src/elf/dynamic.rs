@@ -0,0 +1,142 @@
+use serde::Serialize;
+
+use crate::elf::{Elf, ElfError};
+use crate::elf::helpers::read_str;
+use crate::elf::types::*;
+
+/// An entry of the dynamic array
+#[derive(Debug, Clone, Serialize)]
+pub struct DynEntry {
+    pub tag    : DynTag,
+    pub value  : u64,
+    // Value resolved through `DT_STRTAB` for the tags holding a string
+    pub string : Option<String>,
+}
+
+impl DynEntry {
+    /// The value of entries with this tag is an offset in `DT_STRTAB`
+    pub fn has_string(tag : DynTag) -> bool {
+        matches!(tag, DynTag::DtNeeded | DynTag::DtSoname | DynTag::DtRpath
+                      | DynTag::DtRunpath | DynTag::DtConfig
+                      | DynTag::DtDepAudit | DynTag::DtAudit
+                      | DynTag::DtAuxiliary | DynTag::DtFilter)
+    }
+}
+
+/// Content of the dynamic array, describing how the file is linked at
+/// runtime
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Dynamic {
+    pub entries : Vec<DynEntry>,
+}
+
+impl Dynamic {
+    /// Value of the first entry with the tag `tag`
+    pub fn get(&self, tag : DynTag) -> Option<u64> {
+        self.entries.iter().find(|e| e.tag == tag).map(|e| e.value)
+    }
+
+    /// Resolved strings of the entries with the tag `tag`
+    pub fn strings(&self, tag : DynTag) -> Vec<&str> {
+        self.entries.iter()
+            .filter(|e| e.tag == tag)
+            .filter_map(|e| e.string.as_deref())
+            .collect()
+    }
+
+    /// Libraries the file depends on (`DT_NEEDED`)
+    pub fn needed(&self) -> Vec<&str> {
+        self.strings(DynTag::DtNeeded)
+    }
+
+    /// Name of the shared object (`DT_SONAME`)
+    pub fn soname(&self) -> Option<&str> {
+        self.strings(DynTag::DtSoname).first().copied()
+    }
+
+    /// Library search path (`DT_RPATH`)
+    pub fn rpath(&self) -> Option<&str> {
+        self.strings(DynTag::DtRpath).first().copied()
+    }
+
+    /// Library search path (`DT_RUNPATH`)
+    pub fn runpath(&self) -> Option<&str> {
+        self.strings(DynTag::DtRunpath).first().copied()
+    }
+
+    /// `DF_*` flags, 0 when `DT_FLAGS` is not present
+    pub fn flags(&self) -> u64 {
+        self.get(DynTag::DtFlags).unwrap_or(0)
+    }
+
+    /// `DF_1_*` flags, 0 when `DT_FLAGS_1` is not present
+    pub fn flags_1(&self) -> u64 {
+        self.get(DynTag::DtFlags1).unwrap_or(0)
+    }
+}
+
+impl Elf<'_> {
+    /// Parses the dynamic array, found through `PT_DYNAMIC` or else the
+    /// `SHT_DYNAMIC` section. Returns `None` for statically linked files.
+    pub fn dynamic(&self) -> Result<Option<Dynamic>, ElfError> {
+        let section = self.sections().iter()
+            .find(|shdr| shdr.sh_type == SHType::ShtDYNAMIC);
+        let (offset, size) = match self.segments().iter()
+            .find(|phdr| phdr.p_type == PType::PtDynamic)
+        {
+            Some(phdr) => (phdr.p_offset, phdr.p_filesz),
+            None => match &section {
+                Some(shdr) => (shdr.sh_offset, shdr.sh_size),
+                None => return Ok(None),
+            },
+        };
+
+        let mut raw = self.dynamic_entries(offset, size)?;
+        if let Some(end) = raw.iter().position(|e| e.d_tag == 0) {
+            raw.truncate(end);
+        }
+
+        // Find the string table through DT_STRTAB as the loader does, or
+        // through the section linked to .dynamic if it is not mapped
+        let strtab_addr = raw.iter()
+            .find(|e| DynTag::from(e.d_tag) == DynTag::DtStrTab)
+            .map(|e| e.d_val);
+        let strtab_size = raw.iter()
+            .find(|e| DynTag::from(e.d_tag) == DynTag::DtStrSz)
+            .map(|e| e.d_val);
+        let strtab = match (strtab_addr.and_then(|a| self.vaddr_to_offset(a)),
+                            strtab_size) {
+            (Some(offset), Some(size)) => Some((offset, size)),
+            _ => section
+                .and_then(|shdr| self.sections().get(shdr.sh_link as usize))
+                .filter(|shdr| shdr.sh_type == SHType::ShtSTRTAB)
+                .map(|shdr| (shdr.sh_offset, shdr.sh_size)),
+        };
+        // A table said to run past the end of the file is cut there, only
+        // the strings beyond it are lost
+        let strings = match strtab {
+            Some((offset, size)) => {
+                let size = size.min((self.data().len() as u64)
+                                    .saturating_sub(offset));
+                Some((offset, self.bytes_at(offset, size)?))
+            }
+            None => None,
+        };
+
+        let entries = raw.into_iter().map(|e| {
+            let tag = DynTag::from(e.d_tag);
+            let string = match strings {
+                Some((offset, table)) if DynEntry::has_string(tag) => {
+                    Some(read_str(table, e.d_val).ok_or(ElfError::OutOfRange {
+                        offset : offset.saturating_add(e.d_val),
+                        size   : 1,
+                    })?)
+                }
+                _ => None,
+            };
+            Ok(DynEntry { tag, value : e.d_val, string })
+        }).collect::<Result<Vec<_>, ElfError>>()?;
+
+        Ok(Some(Dynamic { entries }))
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::io;
+
+/// Errors that can happen while parsing an `ELF` file
+///
+/// Every variant carries the file offset at which parsing failed so that a
+/// malformed input can be reported without aborting the whole process.
+#[derive(Debug)]
+pub enum ElfError {
+    /// An I/O error occured while reading the underlying file
+    Io {
+        offset : u64,
+        source : io::Error,
+    },
+    /// The file ended before a structure could be fully read
+    Truncated {
+        offset : u64,
+    },
+    /// The file does not start with `\x7fELF`
+    BadMagic {
+        offset : u64,
+        magic  : [u8; 4],
+    },
+    /// A table or section described by the file lies outside of it
+    OutOfRange {
+        offset : u64,
+        size   : u64,
+    },
+    /// The `EI_CLASS` byte does not match the parser that was used
+    UnsupportedClass {
+        offset : u64,
+        class  : u8,
+    },
+    /// A field holds a value that is not defined by the specification
+    InvalidValue {
+        offset : u64,
+        field  : &'static str,
+        value  : u64,
+    },
+    /// A field giving the layout of a table, such as the size of its
+    /// entries, does not fit the structures the table holds
+    Malformed {
+        offset : u64,
+        field  : &'static str,
+        value  : u64,
+    },
+    /// A section required for the analysis is not present
+    MissingSection {
+        offset : u64,
+        name   : &'static str,
+    },
+    /// A segment required for the analysis is not present
+    MissingSegment {
+        offset : u64,
+        name   : &'static str,
+    },
+}
+
+impl ElfError {
+    /// File offset at which the error was detected
+    pub fn offset(&self) -> u64 {
+        match *self {
+            ElfError::Io { offset, .. }               => offset,
+            ElfError::Truncated { offset }            => offset,
+            ElfError::BadMagic { offset, .. }         => offset,
+            ElfError::OutOfRange { offset, .. }       => offset,
+            ElfError::UnsupportedClass { offset, .. } => offset,
+            ElfError::InvalidValue { offset, .. }     => offset,
+            ElfError::Malformed { offset, .. }        => offset,
+            ElfError::MissingSection { offset, .. }   => offset,
+            ElfError::MissingSegment { offset, .. }   => offset,
+        }
+    }
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfError::Io { offset, source } =>
+                write!(f, "I/O error at offset 0x{:x}: {}", offset, source),
+            ElfError::Truncated { offset } =>
+                write!(f, "file truncated at offset 0x{:x}", offset),
+            ElfError::BadMagic { offset, magic } =>
+                write!(f, "bad magic {:02x?} at offset 0x{:x}", magic, offset),
+            ElfError::OutOfRange { offset, size } =>
+                write!(f, "range 0x{:x}..0x{:x} is outside of the file",
+                       offset, offset.saturating_add(*size)),
+            ElfError::UnsupportedClass { offset, class } =>
+                write!(f, "unsupported class {} at offset 0x{:x}", class, offset),
+            ElfError::InvalidValue { offset, field, value } =>
+                write!(f, "invalid {} 0x{:x} at offset 0x{:x}", field, value, offset),
+            ElfError::Malformed { offset, field, value } =>
+                write!(f, "malformed {} 0x{:x} at offset 0x{:x}", field, value, offset),
+            ElfError::MissingSection { offset, name } =>
+                write!(f, "section {} not found in table at offset 0x{:x}",
+                       name, offset),
+            ElfError::MissingSegment { offset, name } =>
+                write!(f, "segment {} not found in table at offset 0x{:x}",
+                       name, offset),
+        }
+    }
+}
+
+impl std::error::Error for ElfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ElfError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
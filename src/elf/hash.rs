@@ -0,0 +1,413 @@
+use std::io::Cursor;
+use std::slice;
+
+use crate::elf::{Elf, ElfError, Symbol};
+use crate::elf::helpers::{read_str, read_table, read_u32};
+use crate::elf::types::*;
+
+/// Content of a `SHT_HASH` section
+#[derive(Debug, Clone, Default)]
+pub struct SysvHash {
+    pub buckets : Vec<u32>,
+    pub chains  : Vec<u32>,
+}
+
+/// Content of a `SHT_GNU_HASH` section
+#[derive(Debug, Clone, Default)]
+pub struct GnuHash {
+    // Index of the first symbol reachable through the table
+    pub symoffset   : u32,
+    pub bloom_shift : u32,
+    // Words of the bloom filter, as wide as an address
+    pub bloom       : Vec<u64>,
+    pub buckets     : Vec<u32>,
+    pub chain       : Vec<u32>,
+}
+
+/// Hash function of `SHT_HASH` tables
+pub fn sysv_hash(name : &[u8]) -> u32 {
+    let mut h : u32 = 0;
+    for &c in name {
+        h = (h << 4).wrapping_add(c as u32);
+        let g = h & 0xf0000000;
+        if g != 0 {
+            h ^= g >> 24;
+        }
+        h &= !g;
+    }
+    h
+}
+
+/// Hash function of `SHT_GNU_HASH` tables (DJB hash)
+pub fn gnu_hash(name : &[u8]) -> u32 {
+    name.iter().fold(5381u32, |h, &c| h.wrapping_mul(33).wrapping_add(c as u32))
+}
+
+impl SysvHash {
+    /// Indices of the symbols that may be named `name`, the caller still
+    /// has to compare the names
+    pub fn candidates(&self, name : &str) -> Vec<u32> {
+        let mut candidates = Vec::new();
+        if self.buckets.is_empty() {
+            return candidates;
+        }
+
+        let hash = sysv_hash(name.as_bytes());
+        let mut index = self.buckets[(hash as usize) % self.buckets.len()];
+        // A corrupted chain could loop, it cannot be longer than the table
+        while index != 0 && candidates.len() < self.chains.len() {
+            candidates.push(index);
+            index = match self.chains.get(index as usize) {
+                Some(&next) => next,
+                None => break,
+            };
+        }
+        candidates
+    }
+}
+
+impl GnuHash {
+    /// Indices of the symbols that may be named `name`, the caller still
+    /// has to compare the names. `word_bits` is the width of an address.
+    pub fn candidates(&self, name : &str, word_bits : u32) -> Vec<u32> {
+        let mut candidates = Vec::new();
+        if self.buckets.is_empty() || self.bloom.is_empty() {
+            return candidates;
+        }
+        let hash = gnu_hash(name.as_bytes());
+
+        // The bloom filter rejects most of the names that are not defined. A
+        // shift as wide as the hash comes from a corrupted table.
+        let word = self.bloom[((hash / word_bits) as usize) % self.bloom.len()];
+        let shifted = match hash.checked_shr(self.bloom_shift) {
+            Some(shifted) => shifted,
+            None => return candidates,
+        };
+        let mask = (1u64 << (hash % word_bits))
+            | (1u64 << (shifted % word_bits));
+        if word & mask != mask {
+            return candidates;
+        }
+
+        let mut index = self.buckets[(hash as usize) % self.buckets.len()];
+        if index < self.symoffset {
+            return candidates;
+        }
+        // Hashes of a chain are stored in order, the lowest bit marks the
+        // last symbol of the chain
+        while let Some(&chain_hash) = self.chain.get((index - self.symoffset) as usize) {
+            if (chain_hash | 1) == (hash | 1) {
+                candidates.push(index);
+            }
+            if chain_hash & 1 == 1 {
+                break;
+            }
+            index = match index.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        candidates
+    }
+}
+
+impl Elf<'_> {
+    /// Reads `count` 32 bits words located at `offset`
+    fn u32_table(&self, offset : u64, count : u64) -> Result<Vec<u32>, ElfError> {
+        let endian = self.endianness();
+        read_table(self.data(), offset, count.saturating_mul(4), 4,
+                   |io| read_u32(io, endian))
+    }
+
+    /// Parses the `SHT_HASH` table described by `shdr`
+    pub fn sysv_hash_table(&self, shdr : &Elf64Shdr)
+        -> Result<SysvHash, ElfError>
+    {
+        let endian = self.endianness();
+        let mut io = Cursor::new(self.section_data(shdr)?);
+        let nbucket = read_u32(&mut io, endian)? as u64;
+        let nchain = read_u32(&mut io, endian)? as u64;
+
+        let out_of_range = || ElfError::OutOfRange {
+            offset : shdr.sh_offset,
+            size   : shdr.sh_size,
+        };
+        let buckets_offset = shdr.sh_offset.checked_add(8)
+            .ok_or_else(out_of_range)?;
+        let chains_offset = buckets_offset.checked_add(nbucket * 4)
+            .ok_or_else(out_of_range)?;
+        Ok(SysvHash {
+            buckets : self.u32_table(buckets_offset, nbucket)?,
+            chains  : self.u32_table(chains_offset, nchain)?,
+        })
+    }
+
+    /// Parses the `SHT_GNU_HASH` table described by `shdr`
+    pub fn gnu_hash_table(&self, shdr : &Elf64Shdr)
+        -> Result<GnuHash, ElfError>
+    {
+        let endian = self.endianness();
+        let mut io = Cursor::new(self.section_data(shdr)?);
+        let nbuckets = read_u32(&mut io, endian)? as u64;
+        let symoffset = read_u32(&mut io, endian)?;
+        let bloom_size = read_u32(&mut io, endian)? as u64;
+        let bloom_shift = read_u32(&mut io, endian)?;
+
+        let word_size = self.word_size();
+        let offsets = || {
+            let bloom = shdr.sh_offset.checked_add(16)?;
+            let buckets = bloom.checked_add(bloom_size * word_size)?;
+            let chain = buckets.checked_add(nbuckets * 4)?;
+            Some((bloom, buckets, chain, shdr.sh_offset.checked_add(shdr.sh_size)?))
+        };
+        let (bloom_offset, buckets_offset, chain_offset, end) = offsets()
+            .ok_or(ElfError::OutOfRange {
+                offset : shdr.sh_offset,
+                size   : shdr.sh_size,
+            })?;
+        // The chain runs up to the end of the section
+        let chain_count = end.saturating_sub(chain_offset) / 4;
+
+        Ok(GnuHash {
+            symoffset,
+            bloom_shift,
+            bloom   : self.words(bloom_offset, bloom_size * word_size)?,
+            buckets : self.u32_table(buckets_offset, nbuckets)?,
+            chain   : self.u32_table(chain_offset, chain_count)?,
+        })
+    }
+
+    /// Size of an address in bytes
+    fn word_size(&self) -> u64 {
+        match self.class() {
+            EiClass::ElfClass32 => 4,
+            _ => 8,
+        }
+    }
+
+    /// Hash table, dynamic symbol table and dynamic string table used to look
+    /// symbols up, from the section headers or else from the dynamic array.
+    /// The GNU hash table is preferred over the SysV one.
+    fn lookup_tables(&self)
+        -> Result<Option<(Elf64Shdr, Elf64Shdr, Elf64Shdr)>, ElfError>
+    {
+        let sections = self.sections();
+        let table = sections.iter()
+            .find(|shdr| shdr.sh_type == SHType::ShtGnuHash)
+            .or_else(|| sections.iter()
+                     .find(|shdr| shdr.sh_type == SHType::ShtHASH));
+        let table = match table {
+            Some(table) => table,
+            None => return self.dynamic_lookup_tables(),
+        };
+
+        let dynsym = sections.get(table.sh_link as usize)
+            .ok_or(ElfError::MissingSection {
+                offset : self.shoff(),
+                name   : "linked symbol table",
+            })?;
+        let dynstr = sections.get(dynsym.sh_link as usize)
+            .ok_or(ElfError::MissingSection {
+                offset : self.shoff(),
+                name   : "linked string table",
+            })?;
+        Ok(Some((table.clone(), dynsym.clone(), dynstr.clone())))
+    }
+
+    /// Tables used to look symbols up, described by `DT_GNU_HASH` or
+    /// `DT_HASH`, `DT_SYMTAB` and `DT_STRTAB`, for files whose section
+    /// headers were stripped. The size of the tables is not recorded, it is
+    /// deduced from the hash table.
+    pub(crate) fn dynamic_lookup_tables(&self)
+        -> Result<Option<(Elf64Shdr, Elf64Shdr, Elf64Shdr)>, ElfError>
+    {
+        let dynamic = match self.dynamic()? {
+            Some(dynamic) => dynamic,
+            None => return Ok(None),
+        };
+        let offset_of = |tag| dynamic.get(tag)
+            .and_then(|vaddr| self.vaddr_to_offset(vaddr));
+        let (symtab, strtab, strsz) = match (offset_of(DynTag::DtSymTab),
+                                             offset_of(DynTag::DtStrTab),
+                                             dynamic.get(DynTag::DtStrSz)) {
+            (Some(symtab), Some(strtab), Some(strsz)) => (symtab, strtab, strsz),
+            _ => return Ok(None),
+        };
+        let syment = dynamic.get(DynTag::DtSymEnt).unwrap_or(0);
+
+        let (table, nsyms) = if let Some(offset) = offset_of(DynTag::DtGnuHash) {
+            let (size, nsyms) = self.gnu_hash_size(offset)?;
+            (Elf64Shdr {
+                sh_type   : SHType::ShtGnuHash,
+                sh_offset : offset,
+                sh_size   : size,
+                ..Default::default()
+            }, nsyms)
+        } else if let Some(offset) = offset_of(DynTag::DtHash) {
+            let header = self.u32_table(offset, 2)?;
+            let (nbucket, nchain) = (header[0] as u64, header[1] as u64);
+            (Elf64Shdr {
+                sh_type    : SHType::ShtHASH,
+                sh_offset  : offset,
+                sh_size    : (2 + nbucket + nchain) * 4,
+                sh_entsize : 4,
+                ..Default::default()
+            }, nchain)
+        } else {
+            return Ok(None);
+        };
+
+        let entsize = match (syment, self.class()) {
+            (0, EiClass::ElfClass32) => 16,
+            (0, _) => 24,
+            (n, _) => n,
+        };
+        let dynsym = Elf64Shdr {
+            sh_type    : SHType::ShtDYNSYM,
+            sh_offset  : symtab,
+            sh_size    : nsyms.saturating_mul(entsize),
+            sh_entsize : entsize,
+            ..Default::default()
+        };
+        let dynstr = Elf64Shdr {
+            sh_type   : SHType::ShtSTRTAB,
+            sh_offset : strtab,
+            sh_size   : strsz,
+            ..Default::default()
+        };
+        Ok(Some((table, dynsym, dynstr)))
+    }
+
+    /// Size of the `SHT_GNU_HASH` table located at `offset`, and number of
+    /// symbols of the table it indexes. The chain of the highest bucket ends
+    /// with the last symbol.
+    fn gnu_hash_size(&self, offset : u64) -> Result<(u64, u64), ElfError> {
+        let header = self.u32_table(offset, 4)?;
+        let (nbuckets, symoffset, bloom_size) =
+            (header[0] as u64, header[1] as u64, header[2] as u64);
+        let out_of_range = || ElfError::OutOfRange { offset, size : 16 };
+
+        let buckets_offset = (bloom_size * self.word_size())
+            .checked_add(16)
+            .and_then(|size| offset.checked_add(size))
+            .ok_or_else(out_of_range)?;
+        let chain_offset = buckets_offset.checked_add(nbuckets * 4)
+            .ok_or_else(out_of_range)?;
+        let last_bucket = self.u32_table(buckets_offset, nbuckets)?
+            .into_iter().max().unwrap_or(0) as u64;
+
+        let mut nsyms = symoffset;
+        if last_bucket >= symoffset {
+            let mut index = last_bucket;
+            loop {
+                let position = (index - symoffset).checked_mul(4)
+                    .and_then(|position| chain_offset.checked_add(position))
+                    .ok_or_else(out_of_range)?;
+                if self.u32_table(position, 1)?[0] & 1 == 1 {
+                    break;
+                }
+                index += 1;
+            }
+            nsyms = index + 1;
+        }
+        let size = (nsyms - symoffset).checked_mul(4)
+            .and_then(|size| chain_offset.checked_add(size))
+            .map(|end| end - offset)
+            .ok_or_else(out_of_range)?;
+        Ok((size, nsyms))
+    }
+
+    /// Looks `name` up in the dynamic symbol table through its hash table,
+    /// as the dynamic loader does. Only defined symbols with their default
+    /// version are returned. Files without a hash table fall back to a
+    /// linear scan.
+    pub fn lookup_dynamic_symbol(&self, name : &str)
+        -> Result<Option<Symbol>, ElfError>
+    {
+        let (table, dynsym, dynstr) = match self.lookup_tables()? {
+            Some(tables) => tables,
+            None => return Ok(self.dynamic_symbols()?.into_iter()
+                .find(|sym| sym.name == name && sym.shndx != SHN_UNDEF
+                      && !sym.version.as_ref().is_some_and(|v| v.hidden))),
+        };
+        let strings = self.section_data(&dynstr)?;
+
+        let candidates = match table.sh_type {
+            SHType::ShtGnuHash => {
+                let word_bits = self.word_size() as u32 * 8;
+                self.gnu_hash_table(&table)?.candidates(name, word_bits)
+            }
+            _ => self.sysv_hash_table(&table)?.candidates(name),
+        };
+
+        // Without a version to ask for, hidden versions are not candidates
+        let versions = self.version_indices()?;
+        for index in candidates {
+            let sym = self.symbol_entry(&dynsym, index as u64)?;
+            if sym.st_shndx == SHN_UNDEF {
+                continue;
+            }
+            let versym = versions.get(index as usize).copied().unwrap_or(0);
+            if versym & VERSYM_HIDDEN != 0 {
+                continue;
+            }
+            if read_str(strings, sym.st_name as u64).as_deref() == Some(name) {
+                let mut symbol = Symbol::from_entry(name.to_string(), &sym);
+                if !versions.is_empty() {
+                    self.set_versions_at(slice::from_mut(&mut symbol), &[versym])?;
+                }
+                return Ok(Some(symbol));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_functions() {
+        assert_eq!(gnu_hash(b""), 5381);
+        assert_eq!(gnu_hash(b"printf"), 0x156b2bb8);
+        assert_eq!(gnu_hash(b"exit"), 0x7c967e3f);
+        assert_eq!(sysv_hash(b""), 0);
+        assert_eq!(sysv_hash(b"printf"), 0x077905a6);
+        assert_eq!(sysv_hash(b"exit"), 0x0006cf04);
+    }
+
+    #[test]
+    fn corrupted_chains() {
+        // A chain looping on itself stops after as many symbols as the table
+        let table = SysvHash { buckets : vec![1], chains : vec![0, 1, 0] };
+        assert_eq!(table.candidates("printf"), [1, 1, 1]);
+
+        // A bucket below symoffset or a chain without end bit stay in range
+        let hash = gnu_hash(b"printf");
+        let table = GnuHash {
+            symoffset   : 4,
+            bloom_shift : 6,
+            bloom       : vec![u64::MAX],
+            buckets     : vec![2],
+            chain       : vec![hash & !1],
+        };
+        assert!(table.candidates("printf", 64).is_empty());
+        let table = GnuHash { buckets : vec![4], ..table };
+        assert_eq!(table.candidates("printf", 64), [4]);
+
+        // A bloom shift as wide as the hash matches nothing
+        let table = GnuHash { bloom_shift : 40, ..table };
+        assert!(table.candidates("printf", 64).is_empty());
+
+        // The last index of the table ends the chain
+        let table = GnuHash {
+            symoffset   : u32::MAX,
+            bloom_shift : 6,
+            bloom       : vec![u64::MAX],
+            buckets     : vec![u32::MAX],
+            chain       : vec![hash & !1, hash & !1],
+        };
+        assert_eq!(table.candidates("printf", 64), [u32::MAX]);
+    }
+}
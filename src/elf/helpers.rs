@@ -1,13 +1,30 @@
+use std::io::{Cursor, Read};
 use std::mem;
 
+use crate::elf::error::ElfError;
+use crate::elf::types::EiData;
+
+/// Current position of the reader in the file, used to report where
+/// parsing failed
+pub fn position(io : &Cursor<&[u8]>) -> u64 {
+    io.position()
+}
+
+/// Generates a function reading an integer with the byte order given by 
+/// `EI_DATA`
 macro_rules! read_uX {
     ($func_name:ident, $type:ty) => {
-        pub fn $func_name(io : &mut dyn std::io::Read) 
-            -> Option<$type>
+        pub fn $func_name(io : &mut Cursor<&[u8]>, endian : EiData) 
+            -> Result<$type, ElfError>
         {
+            let offset = position(io);
             let mut b = [0; mem::size_of::<$type>() as usize];
-            io.read_exact(&mut b).ok()?;
-            Some(<$type>::from_le_bytes(b))
+            io.read_exact(&mut b)
+                .map_err(|_| ElfError::Truncated { offset })?;
+            Ok(match endian {
+                EiData::ElfData2Msb => <$type>::from_be_bytes(b),
+                _ => <$type>::from_le_bytes(b),
+            })
         }
     }
 }
@@ -17,6 +34,38 @@ read_uX!(read_u16, u16);
 read_uX!(read_u32, u32);
 read_uX!(read_u64, u64);
 
+/// Borrows the `size` bytes located at `offset` in the file
+pub fn slice(data : &[u8], offset : u64, size : u64) 
+    -> Result<&[u8], ElfError>
+{
+    check_table(offset, 1, size, data.len() as u64)?;
+    Ok(&data[offset as usize..(offset + size) as usize])
+}
+
+/// Checks that a table of `count` entries of `entsize` bytes starting at
+/// `offset` lies within a file of `len` bytes
+pub fn check_table(offset : u64, count : u64, entsize : u64, len : u64)
+    -> Result<(), ElfError>
+{
+    let size = count.saturating_mul(entsize);
+    match offset.checked_add(size) {
+        Some(end) if end <= len => Ok(()),
+        _ => Err(ElfError::OutOfRange { offset, size }),
+    }
+}
+
+/// Checks that the entries of `entsize` bytes of the table located at
+/// `offset` are large enough to hold structures of `size` bytes. `field` is
+/// the field of the file giving `entsize`.
+pub fn check_entsize(offset : u64, entsize : u64, size : u64,
+                     field : &'static str) -> Result<(), ElfError>
+{
+    match entsize < size {
+        true => Err(ElfError::Malformed { offset, field, value : entsize }),
+        false => Ok(()),
+    }
+}
+
 /*
 /// Reads 1 byte from the file and convert it into an u8
 pub fn read_u8(io : &mut dyn std::io::Read) -> Option<u8> {
@@ -46,3 +95,32 @@ pub fn read_u64(io : &mut dyn std::io::Read) -> Option<u64> {
     Some(u64::from_le_bytes(b))
 }
 */
+
+/// Parses the table of `size / entsize` entries located at `offset` with
+/// `parse`, which is given a reader positioned on each entry in turn
+pub fn read_table<T, F>(data : &[u8], offset : u64, size : u64, entsize : u64,
+                        mut parse : F) -> Result<Vec<T>, ElfError>
+where
+    F : FnMut(&mut Cursor<&[u8]>) -> Result<T, ElfError>,
+{
+    check_table(offset, 1, size, data.len() as u64)?;
+    if entsize == 0 {
+        return Ok(Vec::new());
+    }
+
+    let count = size / entsize;
+    let mut io = Cursor::new(data);
+    let mut table = Vec::with_capacity(count as usize);
+    for i in 0..count {
+        io.set_position(offset + i * entsize);
+        table.push(parse(&mut io)?);
+    }
+    Ok(table)
+}
+
+/// Reads the NUL terminated string starting at `index` in a string table
+pub fn read_str(table : &[u8], index : u64) -> Option<String> {
+    let bytes = table.get(index as usize..)?;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
@@ -0,0 +1,326 @@
+use std::fmt;
+use std::io::Cursor;
+
+use serde::Serialize;
+
+use crate::elf::{Elf, ElfError};
+use crate::elf::helpers::{read_str, read_u32, read_u64, slice};
+use crate::elf::types::*;
+
+/// A note, as found in `PT_NOTE` segments and `SHT_NOTE` sections
+#[derive(Debug, Clone, Serialize)]
+pub struct Note {
+    pub name   : String,
+    pub n_type : u32,
+    pub desc   : Vec<u8>,
+    // Offset of the note header in the file
+    pub offset : u64,
+}
+
+/// Content of the notes owned by `GNU` that we know how to decode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GnuNote {
+    BuildId(Vec<u8>),
+    AbiTag(AbiTag),
+    GoldVersion(String),
+    Properties(Vec<GnuProperty>),
+}
+
+/// Minimum kernel version the file is built for (`NT_GNU_ABI_TAG`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbiTag {
+    pub os    : u32,
+    pub major : u32,
+    pub minor : u32,
+    pub patch : u32,
+}
+
+/// An entry of `NT_GNU_PROPERTY_TYPE_0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GnuProperty {
+    StackSize(u64),
+    NoCopyOnProtected,
+    X86Feature1And(u32),
+    X86Isa1Needed(u32),
+    AArch64Feature1And(u32),
+    Other { pr_type : u32, data : Vec<u8> },
+}
+
+impl fmt::Display for GnuNote {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GnuNote::BuildId(id) => {
+                write!(f, "Build ID: ")?;
+                id.iter().try_for_each(|b| write!(f, "{:02x}", b))
+            }
+            GnuNote::AbiTag(tag) => {
+                let os = match tag.os {
+                    GNU_ABI_TAG_LINUX    => "Linux",
+                    GNU_ABI_TAG_HURD     => "Hurd",
+                    GNU_ABI_TAG_SOLARIS  => "Solaris",
+                    GNU_ABI_TAG_FREEBSD  => "FreeBSD",
+                    GNU_ABI_TAG_NETBSD   => "NetBSD",
+                    GNU_ABI_TAG_SYLLABLE => "Syllable",
+                    _ => "Unknown",
+                };
+                write!(f, "OS: {}, ABI: {}.{}.{}", 
+                       os, tag.major, tag.minor, tag.patch)
+            }
+            GnuNote::GoldVersion(version) => write!(f, "Version: {}", version),
+            GnuNote::Properties(properties) => {
+                write!(f, "Properties: ")?;
+                for (i, property) in properties.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", property)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for GnuProperty {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        // Names of the bits set in a feature mask
+        let features = |mask : u32, names : &[(u32, &str)]| -> String {
+            let set : Vec<&str> = names.iter()
+                .filter(|(bit, _)| mask & bit != 0)
+                .map(|(_, name)| *name)
+                .collect();
+            if set.is_empty() { "none".to_string() } else { set.join(", ") }
+        };
+
+        match self {
+            GnuProperty::StackSize(size) => write!(f, "stack size: 0x{:x}", size),
+            GnuProperty::NoCopyOnProtected => write!(f, "no copy on protected"),
+            GnuProperty::X86Feature1And(mask) => 
+                write!(f, "x86 feature: {}", features(*mask, &[
+                    (GNU_PROPERTY_X86_FEATURE_1_IBT, "IBT"),
+                    (GNU_PROPERTY_X86_FEATURE_1_SHSTK, "SHSTK"),
+                ])),
+            GnuProperty::X86Isa1Needed(mask) => 
+                write!(f, "x86 ISA needed: 0x{:x}", mask),
+            GnuProperty::AArch64Feature1And(mask) => 
+                write!(f, "AArch64 feature: {}", features(*mask, &[
+                    (GNU_PROPERTY_AARCH64_FEATURE_1_BTI, "BTI"),
+                    (GNU_PROPERTY_AARCH64_FEATURE_1_PAC, "PAC"),
+                ])),
+            GnuProperty::Other { pr_type, data } => 
+                write!(f, "<type 0x{:x}, {} bytes>", pr_type, data.len()),
+        }
+    }
+}
+
+/// Name of a note type owned by `GNU`
+pub fn gnu_note_type_name(n_type : u32) -> Option<&'static str> {
+    match n_type {
+        NT_GNU_ABI_TAG         => Some("NT_GNU_ABI_TAG"),
+        NT_GNU_BUILD_ID        => Some("NT_GNU_BUILD_ID"),
+        NT_GNU_GOLD_VERSION    => Some("NT_GNU_GOLD_VERSION"),
+        NT_GNU_PROPERTY_TYPE_0 => Some("NT_GNU_PROPERTY_TYPE_0"),
+        _ => None,
+    }
+}
+
+/// Rounds `value` up to a multiple of `align`
+fn align_up(value : u64, align : u64) -> u64 {
+    value.saturating_add(align - 1) & !(align - 1)
+}
+
+impl Elf<'_> {
+    /// Notes of the `PT_NOTE` segments, or of the `SHT_NOTE` sections for
+    /// files without program headers
+    pub fn notes(&self) -> Result<Vec<Note>, ElfError> {
+        let segments = self.segments();
+        let regions : Vec<(u64, u64, u64)> =
+            if segments.iter().any(|phdr| phdr.p_type == PType::PtNote) {
+                segments.iter()
+                    .filter(|phdr| phdr.p_type == PType::PtNote)
+                    .map(|phdr| (phdr.p_offset, phdr.p_filesz, phdr.p_align))
+                    .collect()
+            } else {
+                self.sections().iter()
+                    .filter(|shdr| shdr.sh_type == SHType::ShtNOTE)
+                    .map(|shdr| (shdr.sh_offset, shdr.sh_size,
+                                 shdr.sh_addralign))
+                    .collect()
+            };
+
+        let mut notes = Vec::new();
+        for (offset, size, align) in regions {
+            notes.extend(self.notes_in(offset, size, align)?);
+        }
+        Ok(notes)
+    }
+
+    /// Notes held in the `size` bytes located at `offset`. The name and
+    /// descriptor are padded to `align`, which is 8 for notes such as
+    /// `NT_GNU_PROPERTY_TYPE_0` in 64 bits files and 4 otherwise.
+    pub fn notes_in(&self, offset : u64, size : u64, align : u64)
+        -> Result<Vec<Note>, ElfError>
+    {
+        let align = if align == 8 { 8 } else { 4 };
+        let endian = self.endianness();
+        let data = self.bytes_at(offset, size)?;
+
+        let mut notes = Vec::new();
+        let mut pos = 0;
+        while pos + ElfNhdr::SIZE <= size {
+            let mut io = Cursor::new(data);
+            io.set_position(pos);
+            let nhdr = ElfNhdr::from_io(&mut io, endian)
+                .map_err(|_| ElfError::Truncated { offset : offset + pos })?;
+
+            let name_start = pos + ElfNhdr::SIZE;
+            let desc_start = align_up(name_start + nhdr.n_namesz as u64, align);
+            let end = align_up(desc_start + nhdr.n_descsz as u64, align);
+            let name = slice(data, name_start, nhdr.n_namesz as u64)
+                .map_err(|_| ElfError::Truncated { offset : offset + pos })?;
+            let desc = slice(data, desc_start, nhdr.n_descsz as u64)
+                .map_err(|_| ElfError::Truncated { offset : offset + pos })?;
+
+            notes.push(Note {
+                name   : read_str(name, 0).unwrap_or_default(),
+                n_type : nhdr.n_type,
+                desc   : desc.to_vec(),
+                offset : offset + pos,
+            });
+            pos = end;
+        }
+        Ok(notes)
+    }
+
+    /// Decodes a note owned by `GNU`, `None` for other owners and for the
+    /// types we do not know
+    pub fn gnu_note(&self, note : &Note) -> Result<Option<GnuNote>, ElfError> {
+        if note.name != "GNU" {
+            return Ok(None);
+        }
+        let endian = self.endianness();
+        let mut io = Cursor::new(note.desc.as_slice());
+        let truncated = |_| ElfError::Truncated { offset : note.offset };
+
+        Ok(match note.n_type {
+            NT_GNU_BUILD_ID => Some(GnuNote::BuildId(note.desc.clone())),
+            NT_GNU_ABI_TAG => Some(GnuNote::AbiTag(AbiTag {
+                os    : read_u32(&mut io, endian).map_err(truncated)?,
+                major : read_u32(&mut io, endian).map_err(truncated)?,
+                minor : read_u32(&mut io, endian).map_err(truncated)?,
+                patch : read_u32(&mut io, endian).map_err(truncated)?,
+            })),
+            NT_GNU_GOLD_VERSION => Some(GnuNote::GoldVersion(
+                read_str(&note.desc, 0).unwrap_or_default())),
+            NT_GNU_PROPERTY_TYPE_0 =>
+                Some(GnuNote::Properties(self.gnu_properties_in(note)?)),
+            _ => None,
+        })
+    }
+
+    /// Decodes the properties of a `NT_GNU_PROPERTY_TYPE_0` note, each
+    /// padded to the size of an address
+    fn gnu_properties_in(&self, note : &Note)
+        -> Result<Vec<GnuProperty>, ElfError>
+    {
+        let endian = self.endianness();
+        let align = match self.class() {
+            EiClass::ElfClass32 => 4,
+            _ => 8,
+        };
+        let truncated = |_| ElfError::Truncated { offset : note.offset };
+        let size = note.desc.len() as u64;
+        // Property types above 0xc0000000 are processor specific
+        let x86 = matches!(self.machine(), EMachine::Em386 | EMachine::Emx86_64);
+        let aarch64 = *self.machine() == EMachine::EmAArch64;
+
+        let mut properties = Vec::new();
+        let mut pos = 0;
+        while pos + 8 <= size {
+            let mut io = Cursor::new(note.desc.as_slice());
+            io.set_position(pos);
+            let pr_type = read_u32(&mut io, endian).map_err(truncated)?;
+            let pr_datasz = read_u32(&mut io, endian).map_err(truncated)? as u64;
+            let data = slice(&note.desc, pos + 8, pr_datasz)
+                .map_err(truncated)?;
+
+            let mut io = Cursor::new(data);
+            properties.push(match (pr_type, pr_datasz) {
+                (GNU_PROPERTY_STACK_SIZE, 8) =>
+                    GnuProperty::StackSize(read_u64(&mut io, endian)?),
+                (GNU_PROPERTY_STACK_SIZE, 4) =>
+                    GnuProperty::StackSize(read_u32(&mut io, endian)? as u64),
+                (GNU_PROPERTY_NO_COPY_ON_PROTECTED, _) =>
+                    GnuProperty::NoCopyOnProtected,
+                (GNU_PROPERTY_X86_FEATURE_1_AND, 4) if x86 =>
+                    GnuProperty::X86Feature1And(read_u32(&mut io, endian)?),
+                (GNU_PROPERTY_X86_ISA_1_NEEDED, 4) if x86 =>
+                    GnuProperty::X86Isa1Needed(read_u32(&mut io, endian)?),
+                (GNU_PROPERTY_AARCH64_FEATURE_1_AND, 4) if aarch64 =>
+                    GnuProperty::AArch64Feature1And(read_u32(&mut io, endian)?),
+                _ => GnuProperty::Other { pr_type, data : data.to_vec() },
+            });
+            pos = align_up(pos + 8 + pr_datasz, align);
+        }
+        Ok(properties)
+    }
+
+    /// Notes owned by `GNU` that could be decoded
+    pub fn gnu_notes(&self) -> Result<Vec<GnuNote>, ElfError> {
+        let mut notes = Vec::new();
+        for note in self.notes()? {
+            notes.extend(self.gnu_note(&note)?);
+        }
+        Ok(notes)
+    }
+
+    /// Build-id of the file as a lowercase hexadecimal string, the key used
+    /// by debuginfod and symbol servers
+    pub fn build_id(&self) -> Result<Option<String>, ElfError> {
+        Ok(self.gnu_notes()?.into_iter().find_map(|note| match note {
+            GnuNote::BuildId(id) =>
+                Some(id.iter().map(|b| format!("{:02x}", b)).collect()),
+            _ => None,
+        }))
+    }
+
+    /// Content of the `NT_GNU_ABI_TAG` note
+    pub fn abi_tag(&self) -> Result<Option<AbiTag>, ElfError> {
+        Ok(self.gnu_notes()?.into_iter().find_map(|note| match note {
+            GnuNote::AbiTag(tag) => Some(tag),
+            _ => None,
+        }))
+    }
+
+    /// Properties of the `NT_GNU_PROPERTY_TYPE_0` notes
+    pub fn gnu_properties(&self) -> Result<Vec<GnuProperty>, ElfError> {
+        Ok(self.gnu_notes()?.into_iter().flat_map(|note| match note {
+            GnuNote::Properties(properties) => properties,
+            _ => Vec::new(),
+        }).collect())
+    }
+
+    /// Properties the loader acts upon, read from the `PT_GNU_PROPERTY`
+    /// segment or else from the `.note.gnu.property` section
+    pub fn program_properties(&self) -> Result<Vec<GnuProperty>, ElfError> {
+        let region = match self.segments().iter()
+            .find(|phdr| phdr.p_type == PType::PtGnuProperty)
+        {
+            Some(phdr) => Some((phdr.p_offset, phdr.p_filesz, phdr.p_align)),
+            None => self.section_by_name(".note.gnu.property")
+                .map(|shdr| (shdr.sh_offset, shdr.sh_size, shdr.sh_addralign)),
+        };
+        let notes = match region {
+            Some((offset, size, align)) => self.notes_in(offset, size, align)?,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut properties = Vec::new();
+        for note in notes {
+            if let Some(GnuNote::Properties(p)) = self.gnu_note(&note)? {
+                properties.extend(p);
+            }
+        }
+        Ok(properties)
+    }
+}
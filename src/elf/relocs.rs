@@ -0,0 +1,501 @@
+use serde::Serialize;
+
+use crate::elf::{Elf, ElfError};
+use crate::elf::types::*;
+
+/// A relocation entry, with the symbol index and the type split out of
+/// `r_info`
+#[derive(Debug, Clone, Serialize)]
+pub struct Relocation {
+    pub offset : u64,
+    pub sym    : u32,
+    pub r_type : u32,
+    // Only `SHT_RELA` entries carry an explicit addend
+    pub addend : Option<i64>,
+    // Name of the symbol, resolved through the linked symbol table
+    pub symbol : Option<String>,
+}
+
+impl From<Elf64Rel> for Relocation {
+    fn from(rel : Elf64Rel) -> Self {
+        Relocation {
+            offset : rel.r_offset,
+            sym    : (rel.r_info >> 32) as u32,
+            r_type : rel.r_info as u32,
+            addend : None,
+            symbol : None,
+        }
+    }
+}
+
+impl From<Elf64Rela> for Relocation {
+    fn from(rela : Elf64Rela) -> Self {
+        Relocation {
+            offset : rela.r_offset,
+            sym    : (rela.r_info >> 32) as u32,
+            r_type : rela.r_info as u32,
+            addend : Some(rela.r_addend),
+            symbol : None,
+        }
+    }
+}
+
+impl From<Elf32Rel> for Relocation {
+    fn from(rel : Elf32Rel) -> Self {
+        Relocation {
+            offset : rel.r_offset as u64,
+            sym    : rel.r_info >> 8,
+            r_type : rel.r_info & 0xff,
+            addend : None,
+            symbol : None,
+        }
+    }
+}
+
+impl From<Elf32Rela> for Relocation {
+    fn from(rela : Elf32Rela) -> Self {
+        Relocation {
+            offset : rela.r_offset as u64,
+            sym    : rela.r_info >> 8,
+            r_type : rela.r_info & 0xff,
+            addend : Some(rela.r_addend as i64),
+            symbol : None,
+        }
+    }
+}
+
+/// Relocations held by one `SHT_REL`, `SHT_RELA` or `SHT_RELR` section
+#[derive(Debug, Clone, Serialize)]
+pub struct RelocSection {
+    pub section : Elf64Shdr,
+    pub relocs  : Vec<Relocation>,
+}
+
+impl Elf<'_> {
+    /// Relocations of every `SHT_REL`, `SHT_RELA` and `SHT_RELR` section
+    pub fn relocations(&self) -> Result<Vec<RelocSection>, ElfError> {
+        self.sections().iter()
+            .filter(|shdr| matches!(shdr.sh_type, SHType::ShtREL
+                                    | SHType::ShtRELA | SHType::ShtRELR))
+            .map(|shdr| Ok(RelocSection {
+                relocs  : self.relocations_in(shdr)?,
+                section : shdr.clone(),
+            }))
+            .collect()
+    }
+
+    /// Relocations of the section described by `shdr`. The entries of packed
+    /// `SHT_RELR` sections are expanded into one relative relocation per
+    /// address.
+    pub fn relocations_in(&self, shdr : &Elf64Shdr)
+        -> Result<Vec<Relocation>, ElfError>
+    {
+        let mut relocs = match shdr.sh_type {
+            SHType::ShtREL  => self.rel_entries(shdr.sh_offset, shdr.sh_size)?,
+            SHType::ShtRELA => self.rela_entries(shdr.sh_offset, shdr.sh_size)?,
+            SHType::ShtRELR => {
+                let r_type = relative_type(self.machine());
+                let words = self.words(shdr.sh_offset, shdr.sh_size)?;
+                return Ok(self.decode_relr(&words).into_iter()
+                    .map(|offset| Relocation {
+                        offset,
+                        sym    : 0,
+                        r_type,
+                        addend : None,
+                        symbol : None,
+                    })
+                    .collect());
+            }
+            _ => return Ok(Vec::new()),
+        };
+
+        // Resolve symbol names through the linked symbol table, which is
+        // absent for relocations that only use the base address
+        let sections = self.sections();
+        if let Some(symtab) = sections.get(shdr.sh_link as usize)
+            .filter(|s| matches!(s.sh_type, SHType::ShtSYMTAB
+                                 | SHType::ShtDYNSYM))
+        {
+            let symbols = self.symbols_in(symtab)?;
+            for reloc in relocs.iter_mut().filter(|r| r.sym != 0) {
+                reloc.symbol = symbols.get(reloc.sym as usize)
+                    .map(|sym| sym.name.clone());
+            }
+        }
+
+        Ok(relocs)
+    }
+
+    /// Expands a `SHT_RELR` table into the addresses it relocates
+    ///
+    /// An even entry is an address to relocate, an odd entry is a bitmap
+    /// of the words following the last address that also need relocating.
+    pub fn decode_relr(&self, words : &[u64]) -> Vec<u64> {
+        let wordsize : u64 = match self.class() {
+            EiClass::ElfClass32 => 4,
+            _ => 8,
+        };
+        let bits = wordsize * 8 - 1;
+
+        let mut addresses = Vec::new();
+        let mut base = 0u64;
+        for &word in words {
+            if word & 1 == 0 {
+                addresses.push(word);
+                base = word.wrapping_add(wordsize);
+            } else {
+                let mut bitmap = word >> 1;
+                let mut addr = base;
+                while bitmap != 0 {
+                    if bitmap & 1 == 1 {
+                        addresses.push(addr);
+                    }
+                    bitmap >>= 1;
+                    addr = addr.wrapping_add(wordsize);
+                }
+                base = base.wrapping_add(bits * wordsize);
+            }
+        }
+        addresses
+    }
+
+    /// Name of a relocation type for the architecture of the file
+    pub fn reloc_type_name(&self, r_type : u32) -> Option<&'static str> {
+        reloc_type_name(self.machine(), r_type)
+    }
+}
+
+/// Type of the relative relocation of an architecture, which is what
+/// `SHT_RELR` entries stand for
+pub fn relative_type(machine : &EMachine) -> u32 {
+    match machine {
+        EMachine::Emx86_64  => 8,
+        EMachine::Em386     => 8,
+        EMachine::EmAArch64 => 1027,
+        EMachine::EmARM     => 23,
+        EMachine::EmRISCV   => 3,
+        _ => 0,
+    }
+}
+
+/// Name of the relocation type `r_type` on `machine`
+pub fn reloc_type_name(machine : &EMachine, r_type : u32)
+    -> Option<&'static str>
+{
+    let table : &[(u32, &str)] = match machine {
+        EMachine::Emx86_64  => X86_64_RELOCS,
+        EMachine::Em386     => I386_RELOCS,
+        EMachine::EmAArch64 => AARCH64_RELOCS,
+        EMachine::EmARM     => ARM_RELOCS,
+        EMachine::EmRISCV   => RISCV_RELOCS,
+        _ => return None,
+    };
+    table.iter().find(|(value, _)| *value == r_type).map(|(_, name)| *name)
+}
+
+const X86_64_RELOCS : &[(u32, &str)] = &[
+    (0,  "R_X86_64_NONE"),
+    (1,  "R_X86_64_64"),
+    (2,  "R_X86_64_PC32"),
+    (3,  "R_X86_64_GOT32"),
+    (4,  "R_X86_64_PLT32"),
+    (5,  "R_X86_64_COPY"),
+    (6,  "R_X86_64_GLOB_DAT"),
+    (7,  "R_X86_64_JUMP_SLOT"),
+    (8,  "R_X86_64_RELATIVE"),
+    (9,  "R_X86_64_GOTPCREL"),
+    (10, "R_X86_64_32"),
+    (11, "R_X86_64_32S"),
+    (12, "R_X86_64_16"),
+    (13, "R_X86_64_PC16"),
+    (14, "R_X86_64_8"),
+    (15, "R_X86_64_PC8"),
+    (16, "R_X86_64_DTPMOD64"),
+    (17, "R_X86_64_DTPOFF64"),
+    (18, "R_X86_64_TPOFF64"),
+    (19, "R_X86_64_TLSGD"),
+    (20, "R_X86_64_TLSLD"),
+    (21, "R_X86_64_DTPOFF32"),
+    (22, "R_X86_64_GOTTPOFF"),
+    (23, "R_X86_64_TPOFF32"),
+    (24, "R_X86_64_PC64"),
+    (25, "R_X86_64_GOTOFF64"),
+    (26, "R_X86_64_GOTPC32"),
+    (27, "R_X86_64_GOT64"),
+    (28, "R_X86_64_GOTPCREL64"),
+    (29, "R_X86_64_GOTPC64"),
+    (30, "R_X86_64_GOTPLT64"),
+    (31, "R_X86_64_PLTOFF64"),
+    (32, "R_X86_64_SIZE32"),
+    (33, "R_X86_64_SIZE64"),
+    (34, "R_X86_64_GOTPC32_TLSDESC"),
+    (35, "R_X86_64_TLSDESC_CALL"),
+    (36, "R_X86_64_TLSDESC"),
+    (37, "R_X86_64_IRELATIVE"),
+    (38, "R_X86_64_RELATIVE64"),
+    (41, "R_X86_64_GOTPCRELX"),
+    (42, "R_X86_64_REX_GOTPCRELX"),
+    (43, "R_X86_64_CODE_4_GOTPCRELX"),
+];
+
+const I386_RELOCS : &[(u32, &str)] = &[
+    (0,  "R_386_NONE"),
+    (1,  "R_386_32"),
+    (2,  "R_386_PC32"),
+    (3,  "R_386_GOT32"),
+    (4,  "R_386_PLT32"),
+    (5,  "R_386_COPY"),
+    (6,  "R_386_GLOB_DAT"),
+    (7,  "R_386_JUMP_SLOT"),
+    (8,  "R_386_RELATIVE"),
+    (9,  "R_386_GOTOFF"),
+    (10, "R_386_GOTPC"),
+    (11, "R_386_32PLT"),
+    (14, "R_386_TLS_TPOFF"),
+    (15, "R_386_TLS_IE"),
+    (16, "R_386_TLS_GOTIE"),
+    (17, "R_386_TLS_LE"),
+    (18, "R_386_TLS_GD"),
+    (19, "R_386_TLS_LDM"),
+    (20, "R_386_16"),
+    (21, "R_386_PC16"),
+    (22, "R_386_8"),
+    (23, "R_386_PC8"),
+    (24, "R_386_TLS_GD_32"),
+    (25, "R_386_TLS_GD_PUSH"),
+    (26, "R_386_TLS_GD_CALL"),
+    (27, "R_386_TLS_GD_POP"),
+    (28, "R_386_TLS_LDM_32"),
+    (29, "R_386_TLS_LDM_PUSH"),
+    (30, "R_386_TLS_LDM_CALL"),
+    (31, "R_386_TLS_LDM_POP"),
+    (32, "R_386_TLS_LDO_32"),
+    (33, "R_386_TLS_IE_32"),
+    (34, "R_386_TLS_LE_32"),
+    (35, "R_386_TLS_DTPMOD32"),
+    (36, "R_386_TLS_DTPOFF32"),
+    (37, "R_386_TLS_TPOFF32"),
+    (38, "R_386_SIZE32"),
+    (39, "R_386_TLS_GOTDESC"),
+    (40, "R_386_TLS_DESC_CALL"),
+    (41, "R_386_TLS_DESC"),
+    (42, "R_386_IRELATIVE"),
+    (43, "R_386_GOT32X"),
+];
+
+const AARCH64_RELOCS : &[(u32, &str)] = &[
+    (0,    "R_AARCH64_NONE"),
+    (256,  "R_AARCH64_NONE"),
+    (257,  "R_AARCH64_ABS64"),
+    (258,  "R_AARCH64_ABS32"),
+    (259,  "R_AARCH64_ABS16"),
+    (260,  "R_AARCH64_PREL64"),
+    (261,  "R_AARCH64_PREL32"),
+    (262,  "R_AARCH64_PREL16"),
+    (263,  "R_AARCH64_MOVW_UABS_G0"),
+    (264,  "R_AARCH64_MOVW_UABS_G0_NC"),
+    (265,  "R_AARCH64_MOVW_UABS_G1"),
+    (266,  "R_AARCH64_MOVW_UABS_G1_NC"),
+    (267,  "R_AARCH64_MOVW_UABS_G2"),
+    (268,  "R_AARCH64_MOVW_UABS_G2_NC"),
+    (269,  "R_AARCH64_MOVW_UABS_G3"),
+    (270,  "R_AARCH64_MOVW_SABS_G0"),
+    (271,  "R_AARCH64_MOVW_SABS_G1"),
+    (272,  "R_AARCH64_MOVW_SABS_G2"),
+    (273,  "R_AARCH64_LD_PREL_LO19"),
+    (274,  "R_AARCH64_ADR_PREL_LO21"),
+    (275,  "R_AARCH64_ADR_PREL_PG_HI21"),
+    (276,  "R_AARCH64_ADR_PREL_PG_HI21_NC"),
+    (277,  "R_AARCH64_ADD_ABS_LO12_NC"),
+    (278,  "R_AARCH64_LDST8_ABS_LO12_NC"),
+    (279,  "R_AARCH64_TSTBR14"),
+    (280,  "R_AARCH64_CONDBR19"),
+    (282,  "R_AARCH64_JUMP26"),
+    (283,  "R_AARCH64_CALL26"),
+    (284,  "R_AARCH64_LDST16_ABS_LO12_NC"),
+    (285,  "R_AARCH64_LDST32_ABS_LO12_NC"),
+    (286,  "R_AARCH64_LDST64_ABS_LO12_NC"),
+    (287,  "R_AARCH64_MOVW_PREL_G0"),
+    (288,  "R_AARCH64_MOVW_PREL_G0_NC"),
+    (289,  "R_AARCH64_MOVW_PREL_G1"),
+    (290,  "R_AARCH64_MOVW_PREL_G1_NC"),
+    (291,  "R_AARCH64_MOVW_PREL_G2"),
+    (292,  "R_AARCH64_MOVW_PREL_G2_NC"),
+    (293,  "R_AARCH64_MOVW_PREL_G3"),
+    (299,  "R_AARCH64_LDST128_ABS_LO12_NC"),
+    (311,  "R_AARCH64_ADR_GOT_PAGE"),
+    (312,  "R_AARCH64_LD64_GOT_LO12_NC"),
+    (313,  "R_AARCH64_LD64_GOTPAGE_LO15"),
+    (512,  "R_AARCH64_TLSGD_ADR_PREL21"),
+    (513,  "R_AARCH64_TLSGD_ADR_PAGE21"),
+    (514,  "R_AARCH64_TLSGD_ADD_LO12_NC"),
+    (541,  "R_AARCH64_TLSIE_ADR_GOTTPREL_PAGE21"),
+    (542,  "R_AARCH64_TLSIE_LD64_GOTTPREL_LO12_NC"),
+    (549,  "R_AARCH64_TLSLE_ADD_TPREL_HI12"),
+    (550,  "R_AARCH64_TLSLE_ADD_TPREL_LO12"),
+    (551,  "R_AARCH64_TLSLE_ADD_TPREL_LO12_NC"),
+    (562,  "R_AARCH64_TLSDESC_ADR_PAGE21"),
+    (563,  "R_AARCH64_TLSDESC_LD64_LO12"),
+    (564,  "R_AARCH64_TLSDESC_ADD_LO12"),
+    (569,  "R_AARCH64_TLSDESC_CALL"),
+    (1024, "R_AARCH64_COPY"),
+    (1025, "R_AARCH64_GLOB_DAT"),
+    (1026, "R_AARCH64_JUMP_SLOT"),
+    (1027, "R_AARCH64_RELATIVE"),
+    (1028, "R_AARCH64_TLS_DTPMOD"),
+    (1029, "R_AARCH64_TLS_DTPREL"),
+    (1030, "R_AARCH64_TLS_TPREL"),
+    (1031, "R_AARCH64_TLSDESC"),
+    (1032, "R_AARCH64_IRELATIVE"),
+];
+
+const ARM_RELOCS : &[(u32, &str)] = &[
+    (0,   "R_ARM_NONE"),
+    (1,   "R_ARM_PC24"),
+    (2,   "R_ARM_ABS32"),
+    (3,   "R_ARM_REL32"),
+    (4,   "R_ARM_LDR_PC_G0"),
+    (5,   "R_ARM_ABS16"),
+    (6,   "R_ARM_ABS12"),
+    (7,   "R_ARM_THM_ABS5"),
+    (8,   "R_ARM_ABS8"),
+    (9,   "R_ARM_SBREL32"),
+    (10,  "R_ARM_THM_CALL"),
+    (11,  "R_ARM_THM_PC8"),
+    (12,  "R_ARM_BREL_ADJ"),
+    (13,  "R_ARM_TLS_DESC"),
+    (17,  "R_ARM_TLS_DTPMOD32"),
+    (18,  "R_ARM_TLS_DTPOFF32"),
+    (19,  "R_ARM_TLS_TPOFF32"),
+    (20,  "R_ARM_COPY"),
+    (21,  "R_ARM_GLOB_DAT"),
+    (22,  "R_ARM_JUMP_SLOT"),
+    (23,  "R_ARM_RELATIVE"),
+    (24,  "R_ARM_GOTOFF32"),
+    (25,  "R_ARM_BASE_PREL"),
+    (26,  "R_ARM_GOT_BREL"),
+    (27,  "R_ARM_PLT32"),
+    (28,  "R_ARM_CALL"),
+    (29,  "R_ARM_JUMP24"),
+    (30,  "R_ARM_THM_JUMP24"),
+    (31,  "R_ARM_BASE_ABS"),
+    (38,  "R_ARM_TARGET1"),
+    (40,  "R_ARM_V4BX"),
+    (41,  "R_ARM_TARGET2"),
+    (42,  "R_ARM_PREL31"),
+    (43,  "R_ARM_MOVW_ABS_NC"),
+    (44,  "R_ARM_MOVT_ABS"),
+    (45,  "R_ARM_MOVW_PREL_NC"),
+    (46,  "R_ARM_MOVT_PREL"),
+    (47,  "R_ARM_THM_MOVW_ABS_NC"),
+    (48,  "R_ARM_THM_MOVT_ABS"),
+    (49,  "R_ARM_THM_MOVW_PREL_NC"),
+    (50,  "R_ARM_THM_MOVT_PREL"),
+    (51,  "R_ARM_THM_JUMP19"),
+    (95,  "R_ARM_GOT_ABS"),
+    (96,  "R_ARM_GOT_PREL"),
+    (102, "R_ARM_THM_JUMP11"),
+    (103, "R_ARM_THM_JUMP8"),
+    (104, "R_ARM_TLS_GD32"),
+    (105, "R_ARM_TLS_LDM32"),
+    (106, "R_ARM_TLS_LDO32"),
+    (107, "R_ARM_TLS_IE32"),
+    (108, "R_ARM_TLS_LE32"),
+    (160, "R_ARM_IRELATIVE"),
+];
+
+const RISCV_RELOCS : &[(u32, &str)] = &[
+    (0,  "R_RISCV_NONE"),
+    (1,  "R_RISCV_32"),
+    (2,  "R_RISCV_64"),
+    (3,  "R_RISCV_RELATIVE"),
+    (4,  "R_RISCV_COPY"),
+    (5,  "R_RISCV_JUMP_SLOT"),
+    (6,  "R_RISCV_TLS_DTPMOD32"),
+    (7,  "R_RISCV_TLS_DTPMOD64"),
+    (8,  "R_RISCV_TLS_DTPREL32"),
+    (9,  "R_RISCV_TLS_DTPREL64"),
+    (10, "R_RISCV_TLS_TPREL32"),
+    (11, "R_RISCV_TLS_TPREL64"),
+    (12, "R_RISCV_TLSDESC"),
+    (16, "R_RISCV_BRANCH"),
+    (17, "R_RISCV_JAL"),
+    (18, "R_RISCV_CALL"),
+    (19, "R_RISCV_CALL_PLT"),
+    (20, "R_RISCV_GOT_HI20"),
+    (21, "R_RISCV_TLS_GOT_HI20"),
+    (22, "R_RISCV_TLS_GD_HI20"),
+    (23, "R_RISCV_PCREL_HI20"),
+    (24, "R_RISCV_PCREL_LO12_I"),
+    (25, "R_RISCV_PCREL_LO12_S"),
+    (26, "R_RISCV_HI20"),
+    (27, "R_RISCV_LO12_I"),
+    (28, "R_RISCV_LO12_S"),
+    (29, "R_RISCV_TPREL_HI20"),
+    (30, "R_RISCV_TPREL_LO12_I"),
+    (31, "R_RISCV_TPREL_LO12_S"),
+    (32, "R_RISCV_TPREL_ADD"),
+    (33, "R_RISCV_ADD8"),
+    (34, "R_RISCV_ADD16"),
+    (35, "R_RISCV_ADD32"),
+    (36, "R_RISCV_ADD64"),
+    (37, "R_RISCV_SUB8"),
+    (38, "R_RISCV_SUB16"),
+    (39, "R_RISCV_SUB32"),
+    (40, "R_RISCV_SUB64"),
+    (41, "R_RISCV_GOT32_PCREL"),
+    (43, "R_RISCV_ALIGN"),
+    (44, "R_RISCV_RVC_BRANCH"),
+    (45, "R_RISCV_RVC_JUMP"),
+    (51, "R_RISCV_RELAX"),
+    (52, "R_RISCV_SUB6"),
+    (53, "R_RISCV_SET6"),
+    (54, "R_RISCV_SET8"),
+    (55, "R_RISCV_SET16"),
+    (56, "R_RISCV_SET32"),
+    (57, "R_RISCV_32_PCREL"),
+    (58, "R_RISCV_IRELATIVE"),
+    (59, "R_RISCV_PLT32"),
+    (60, "R_RISCV_SET_ULEB128"),
+    (61, "R_RISCV_SUB_ULEB128"),
+    (62, "R_RISCV_TLSDESC_HI20"),
+    (63, "R_RISCV_TLSDESC_LOAD_LO12"),
+    (64, "R_RISCV_TLSDESC_ADD_LO12"),
+    (65, "R_RISCV_TLSDESC_CALL"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn r_info_split() {
+        let rela = Relocation::from(Elf64Rela {
+            r_offset : 0x3fd8,
+            r_info   : (5 << 32) | 7,
+            r_addend : -8,
+        });
+        assert_eq!((rela.offset, rela.sym, rela.r_type, rela.addend),
+                   (0x3fd8, 5, 7, Some(-8)));
+
+        let rel = Relocation::from(Elf64Rel {
+            r_offset : 0,
+            r_info   : 0x1_0000_0008,
+        });
+        assert_eq!((rel.sym, rel.r_type, rel.addend), (1, 8, None));
+
+        let rel = Relocation::from(Elf32Rel {
+            r_offset : 0x2000,
+            r_info   : (5 << 8) | 7,
+        });
+        assert_eq!((rel.offset, rel.sym, rel.r_type), (0x2000, 5, 7));
+
+        let rela = Relocation::from(Elf32Rela {
+            r_offset : 0x2000,
+            r_info   : (0x123456 << 8) | 0x2a,
+            r_addend : -4,
+        });
+        assert_eq!((rela.sym, rela.r_type, rela.addend),
+                   (0x123456, 0x2a, Some(-4)));
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::elf::{Elf, ElfError, Symbol};
+use crate::elf::types::*;
+
+/// What an address was resolved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LocationKind {
+    Symbol,
+    Section,
+}
+
+/// Symbol or section containing an address, and the offset of the address
+/// from its start
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub name   : String,
+    pub offset : u64,
+    pub kind   : LocationKind,
+}
+
+impl Location {
+    /// Replaces the name of the symbol by its demangled form, when it is a
+    /// mangled C++ or Rust name
+    #[cfg(feature = "demangle")]
+    pub fn demangle(&mut self) {
+        if self.kind != LocationKind::Symbol {
+            return;
+        }
+        if let Some(name) = crate::elf::demangle::demangle(&self.name) {
+            self.name = name;
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self.offset {
+            0 => write!(f, "{}", self.name),
+            offset => write!(f, "{}+0x{:x}", self.name, offset),
+        }
+    }
+}
+
+/// Range of addresses covered by a symbol or a section
+#[derive(Debug, Clone)]
+struct Range {
+    start : u64,
+    size  : u64,
+    name  : String,
+}
+
+impl Range {
+    fn contains(&self, addr : u64) -> bool {
+        // A symbol without a size only covers its own address
+        addr >= self.start && addr - self.start < self.size.max(1)
+    }
+}
+
+/// Resolves addresses to the symbol containing them, built once per file
+/// so that each lookup is a binary search
+#[derive(Debug, Clone, Default)]
+pub struct Symbolizer {
+    // Sorted by start address
+    symbols  : Vec<Range>,
+    // Largest symbol, bounds how far back a lookup has to search
+    max_size : u64,
+    sections : Vec<Range>,
+}
+
+impl Symbolizer {
+    /// Symbol containing `addr`, or the section containing it when no
+    /// symbol does
+    pub fn lookup(&self, addr : u64) -> Option<Location> {
+        let end = self.symbols.partition_point(|sym| sym.start <= addr);
+        // Symbols may overlap, the closest start that contains the address
+        // wins
+        let symbol = self.symbols[..end].iter().rev()
+            .take_while(|sym| addr - sym.start <= self.max_size)
+            .find(|sym| sym.contains(addr));
+        if let Some(symbol) = symbol {
+            return Some(Location {
+                name   : symbol.name.clone(),
+                offset : addr - symbol.start,
+                kind   : LocationKind::Symbol,
+            });
+        }
+
+        self.sections.iter()
+            .find(|section| section.contains(addr))
+            .map(|section| Location {
+                name   : section.name.clone(),
+                offset : addr - section.start,
+                kind   : LocationKind::Section,
+            })
+    }
+}
+
+impl Symbolizer {
+    /// Builds the tables from the functions and objects of `symbols`, whose
+    /// section indices refer to `sections`. `thumb` clears the lowest bit of
+    /// the functions, which selects the Thumb mode on ARM.
+    fn new(symbols : Vec<Symbol>, sections : &[Elf64Shdr], thumb : bool)
+        -> Symbolizer
+    {
+        // Symbols with the end of their section when they are functions
+        // without a size
+        let mut ranges : Vec<(Range, Option<u64>)> = symbols.into_iter()
+            .filter(|sym| !sym.name.is_empty())
+            .filter(|sym| !matches!(sym.shndx,
+                                    SHN_UNDEF | SHN_ABS | SHN_COMMON))
+            .filter(|sym| matches!(sym.sym_type, SymType::SttFunc
+                                   | SymType::SttGnuIfunc | SymType::SttObject))
+            .map(|sym| {
+                let start = match thumb && sym.sym_type == SymType::SttFunc {
+                    true => sym.value & !1,
+                    false => sym.value,
+                };
+                let section_end = sections.get(sym.shndx as usize)
+                    .filter(|_| sym.size == 0
+                            && sym.sym_type != SymType::SttObject)
+                    .map(|shdr| shdr.sh_addr.saturating_add(shdr.sh_size));
+                (Range { start, size : sym.size, name : sym.name }, section_end)
+            })
+            .collect();
+        // The dynamic symbols repeat the exported ones of `.symtab`
+        ranges.sort_by(|(a, _), (b, _)| {
+            (a.start, &a.name).cmp(&(b.start, &b.name))
+        });
+        ranges.dedup_by(|(a, _), (b, _)| {
+            a.start == b.start && a.name == b.name
+        });
+
+        // Functions without a size, often written in assembly, are extended
+        // up to the next symbol within their section
+        let starts : Vec<u64> = ranges.iter().map(|(r, _)| r.start).collect();
+        let ranges : Vec<Range> = ranges.into_iter()
+            .map(|(mut range, section_end)| {
+                if let Some(section_end) = section_end {
+                    let next = starts.partition_point(|&s| s <= range.start);
+                    let end = starts.get(next).map_or(section_end,
+                                                      |&s| s.min(section_end));
+                    range.size = end.saturating_sub(range.start);
+                }
+                range
+            })
+            .collect();
+
+        let sections = sections.iter()
+            .filter(|shdr| shdr.sh_flags & SHF_ALLOC != 0 && shdr.sh_addr != 0)
+            .map(|shdr| Range {
+                start : shdr.sh_addr,
+                size  : shdr.sh_size,
+                name  : shdr.name.clone(),
+            })
+            .collect();
+
+        Symbolizer {
+            max_size : ranges.iter().map(|r| r.size).max().unwrap_or(0),
+            symbols  : ranges,
+            sections,
+        }
+    }
+}
+
+impl Elf<'_> {
+    /// Builds the tables resolving the addresses of the file, from the
+    /// functions and objects of `.symtab` and `.dynsym`
+    pub fn symbolizer(&self) -> Result<Symbolizer, ElfError> {
+        let thumb = *self.machine() == EMachine::EmARM;
+        let mut symbols = self.symbols()?;
+        symbols.extend(self.dynamic_symbols()?);
+        Ok(Symbolizer::new(symbols, self.sections(), thumb))
+    }
+
+    /// Symbol containing `addr`, as `main+0x1c`, falling back to the
+    /// section containing it. Build a `Symbolizer` to resolve many
+    /// addresses.
+    pub fn symbolize(&self, addr : u64) -> Result<Option<Location>, ElfError> {
+        Ok(self.symbolizer()?.lookup(addr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Section of index 1, `.text`, or 2, `.data`, as laid out by `sections`
+    fn symbol(name : &str, value : u64, size : u64, sym_type : SymType,
+              shndx : u16) -> Symbol
+    {
+        Symbol {
+            name       : name.to_string(),
+            value,
+            size,
+            bind       : SymBind::StbGlobal,
+            sym_type,
+            visibility : SymVisibility::StvDefault,
+            shndx,
+            version    : None,
+        }
+    }
+
+    fn function(name : &str, value : u64, size : u64) -> Symbol {
+        symbol(name, value, size, SymType::SttFunc, 1)
+    }
+
+    /// `.text` at 0x1000 and `.data` at 0x2000, 0x100 bytes each
+    fn sections() -> Vec<Elf64Shdr> {
+        let section = |name : &str, addr| Elf64Shdr {
+            name     : name.to_string(),
+            sh_addr  : addr,
+            sh_size  : 0x100,
+            sh_flags : SHF_ALLOC,
+            ..Default::default()
+        };
+        vec![Elf64Shdr::default(), section(".text", 0x1000),
+             section(".data", 0x2000)]
+    }
+
+    fn name(symbolizer : &Symbolizer, addr : u64) -> Option<String> {
+        symbolizer.lookup(addr).map(|location| location.to_string())
+    }
+
+    #[test]
+    fn overlapping_symbols() {
+        // An alias, a symbol nested in a larger one, and a later symbol
+        let symbolizer = Symbolizer::new(vec![
+            function("outer", 0x1000, 0x40),
+            function("inner", 0x1010, 0x8),
+            function("alias", 0x1010, 0x8),
+            function("after", 0x1040, 0x10),
+        ], &sections(), false);
+
+        assert_eq!(name(&symbolizer, 0x1004).as_deref(), Some("outer+0x4"));
+        // The closest start wins, aliases are searched from the last name
+        assert_eq!(name(&symbolizer, 0x1014).as_deref(), Some("inner+0x4"));
+        // Past the end of the nested symbol, the larger one still contains
+        // the address
+        assert_eq!(name(&symbolizer, 0x1020).as_deref(), Some("outer+0x20"));
+        assert_eq!(name(&symbolizer, 0x1040).as_deref(), Some("after"));
+    }
+
+    #[test]
+    fn zero_size_functions() {
+        let symbolizer = Symbolizer::new(vec![
+            function("start", 0x1000, 0),
+            function("next", 0x1020, 0x10),
+            // The last function of .text ends with the section
+            function("last", 0x10f0, 0),
+            // Objects without a size only cover their own address
+            symbol("flag", 0x2000, 0, SymType::SttObject, 2),
+        ], &sections(), false);
+
+        assert_eq!(name(&symbolizer, 0x101f).as_deref(), Some("start+0x1f"));
+        assert_eq!(name(&symbolizer, 0x1020).as_deref(), Some("next"));
+        assert_eq!(name(&symbolizer, 0x10ff).as_deref(), Some("last+0xf"));
+        assert_eq!(name(&symbolizer, 0x1100), None);
+        assert_eq!(name(&symbolizer, 0x2000).as_deref(), Some("flag"));
+        assert_eq!(name(&symbolizer, 0x2001).as_deref(), Some(".data+0x1"));
+    }
+
+    #[test]
+    fn section_end_overflow() {
+        // A section reaching the end of the address space
+        let mut sections = sections();
+        sections[1].sh_addr = u64::MAX - 0x10;
+        let symbolizer = Symbolizer::new(vec![
+            function("top", u64::MAX - 0x8, 0),
+        ], &sections, false);
+
+        assert_eq!(name(&symbolizer, u64::MAX - 1).as_deref(),
+                   Some("top+0x7"));
+    }
+
+    #[test]
+    fn thumb_functions() {
+        let symbols = || vec![
+            function("thumb", 0x1001, 0x10),
+            symbol("table", 0x2001, 0x4, SymType::SttObject, 2),
+        ];
+
+        // The lowest bit of functions is cleared, not the one of objects
+        let symbolizer = Symbolizer::new(symbols(), &sections(), true);
+        assert_eq!(name(&symbolizer, 0x1000).as_deref(), Some("thumb"));
+        assert_eq!(name(&symbolizer, 0x100f).as_deref(), Some("thumb+0xf"));
+        assert_eq!(name(&symbolizer, 0x2001).as_deref(), Some("table"));
+        assert_eq!(name(&symbolizer, 0x2000).as_deref(), Some(".data"));
+
+        let symbolizer = Symbolizer::new(symbols(), &sections(), false);
+        assert_eq!(name(&symbolizer, 0x1000).as_deref(), Some(".text"));
+        assert_eq!(name(&symbolizer, 0x1010).as_deref(), Some("thumb+0xf"));
+    }
+
+    #[test]
+    fn section_fallback() {
+        let symbolizer = Symbolizer::new(vec![
+            function("main", 0x1000, 0x10),
+            // Undefined, absolute and untyped symbols are not resolved to
+            symbol("puts", 0, 0, SymType::SttFunc, SHN_UNDEF),
+            symbol("absolute", 0x1080, 0x10, SymType::SttFunc, SHN_ABS),
+            symbol("label", 0x1040, 0x10, SymType::SttNotype, 1),
+        ], &sections(), false);
+
+        let location = symbolizer.lookup(0x1010).unwrap();
+        assert_eq!(location, Location {
+            name   : ".text".to_string(),
+            offset : 0x10,
+            kind   : LocationKind::Section,
+        });
+        assert_eq!(name(&symbolizer, 0x1084).as_deref(), Some(".text+0x84"));
+        assert_eq!(name(&symbolizer, 0x1044).as_deref(), Some(".text+0x44"));
+        assert_eq!(symbolizer.lookup(0x1000).unwrap().kind,
+                   LocationKind::Symbol);
+        // Outside of any allocated section
+        assert_eq!(symbolizer.lookup(0), None);
+        assert_eq!(symbolizer.lookup(0x3000), None);
+    }
+}
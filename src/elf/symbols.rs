@@ -0,0 +1,155 @@
+use serde::Serialize;
+
+use crate::elf::{Elf, ElfError, SymbolVersion};
+use crate::elf::helpers::read_str;
+use crate::elf::types::*;
+
+/// A symbol with its name resolved through the linked string table
+#[derive(Debug, Clone, Serialize)]
+pub struct Symbol {
+    pub name       : String,
+    pub value      : u64,
+    pub size       : u64,
+    pub bind       : SymBind,
+    pub sym_type   : SymType,
+    pub visibility : SymVisibility,
+    pub shndx      : u16,
+    // Version of the dynamic symbols of versioned files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version    : Option<SymbolVersion>,
+}
+
+impl Symbol {
+    /// Builds a symbol from its entry in the symbol table and its name
+    pub fn from_entry(name : String, sym : &Elf64Sym) -> Symbol {
+        Symbol {
+            name,
+            value      : sym.st_value,
+            size       : sym.st_size,
+            bind       : sym.bind(),
+            sym_type   : sym.sym_type(),
+            visibility : sym.visibility(),
+            shndx      : sym.st_shndx,
+            version    : None,
+        }
+    }
+
+    /// Replaces the name of the symbol by its demangled form, when it is a
+    /// mangled C++ or Rust name
+    #[cfg(feature = "demangle")]
+    pub fn demangle(&mut self) {
+        if let Some(name) = crate::elf::demangle::demangle(&self.name) {
+            self.name = name;
+        }
+    }
+
+    /// Name of the symbol followed by its version, as `memcpy@GLIBC_2.14`.
+    /// The default version of a defined symbol is marked by `@@`.
+    pub fn versioned_name(&self) -> String {
+        match &self.version {
+            Some(version) if version.hidden || self.shndx == SHN_UNDEF =>
+                format!("{}@{}", self.name, version.name),
+            Some(version) => format!("{}@@{}", self.name, version.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// The symbol is referenced by the file but defined elsewhere
+    pub fn is_import(&self) -> bool {
+        self.shndx == SHN_UNDEF && !self.name.is_empty()
+    }
+
+    /// The symbol is defined by the file and visible to other objects
+    pub fn is_export(&self) -> bool {
+        self.shndx != SHN_UNDEF
+            && matches!(self.bind, SymBind::StbGlobal | SymBind::StbWeak
+                                   | SymBind::StbGnuUnique)
+            && matches!(self.visibility, SymVisibility::StvDefault
+                                         | SymVisibility::StvProtected)
+    }
+}
+
+impl Elf<'_> {
+    /// Symbols of the static symbol tables (`.symtab`)
+    pub fn symbols(&self) -> Result<Vec<Symbol>, ElfError> {
+        self.symbols_of_type(SHType::ShtSYMTAB)
+    }
+
+    /// Symbols of the dynamic symbol tables (`.dynsym`). Without section
+    /// headers, the table is found through `DT_SYMTAB` and `DT_STRTAB`, with
+    /// as many symbols as its hash table indexes.
+    pub fn dynamic_symbols(&self) -> Result<Vec<Symbol>, ElfError> {
+        if self.sections().iter().any(|s| s.sh_type == SHType::ShtDYNSYM) {
+            return self.symbols_of_type(SHType::ShtDYNSYM);
+        }
+        match self.dynamic_lookup_tables()? {
+            Some((_, dynsym, dynstr)) => self.symbols_with_strings(&dynsym,
+                                                                   &dynstr),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Symbols imported by the file, from the dynamic symbol table
+    pub fn imports(&self) -> Result<Vec<Symbol>, ElfError> {
+        Ok(self.dynamic_symbols()?.into_iter()
+            .filter(Symbol::is_import)
+            .collect())
+    }
+
+    /// Symbols exported by the file, from the dynamic symbol table
+    pub fn exports(&self) -> Result<Vec<Symbol>, ElfError> {
+        Ok(self.dynamic_symbols()?.into_iter()
+            .filter(Symbol::is_export)
+            .collect())
+    }
+
+    /// Symbols of the symbol table described by `shdr`, with their names
+    /// resolved through the string table given by its `sh_link`. Dynamic
+    /// symbols also get their version.
+    pub fn symbols_in(&self, shdr : &Elf64Shdr)
+        -> Result<Vec<Symbol>, ElfError>
+    {
+        let strtab_section = self.sections().get(shdr.sh_link as usize)
+            .ok_or(ElfError::MissingSection {
+                offset : self.shoff(),
+                name   : "linked string table",
+            })?;
+        self.symbols_with_strings(shdr, strtab_section)
+    }
+
+    /// Symbols of the symbol table described by `shdr`, with their names
+    /// resolved through the string table described by `strtab_section`
+    fn symbols_with_strings(&self, shdr : &Elf64Shdr,
+                            strtab_section : &Elf64Shdr)
+        -> Result<Vec<Symbol>, ElfError>
+    {
+        let strtab = self.section_data(strtab_section)?;
+
+        let mut symbols = self.symbol_entries(shdr)?.into_iter().map(|sym| {
+            let name = read_str(strtab, sym.st_name as u64)
+                .ok_or(ElfError::OutOfRange {
+                    offset : strtab_section.sh_offset
+                        .saturating_add(sym.st_name as u64),
+                    size   : 1,
+                })?;
+
+            Ok(Symbol::from_entry(name, &sym))
+        }).collect::<Result<Vec<Symbol>, ElfError>>()?;
+
+        if shdr.sh_type == SHType::ShtDYNSYM {
+            self.set_versions(&mut symbols)?;
+        }
+        Ok(symbols)
+    }
+
+    /// Symbols of every section of type `sh_type`
+    fn symbols_of_type(&self, sh_type : SHType)
+        -> Result<Vec<Symbol>, ElfError>
+    {
+        let mut symbols = Vec::new();
+        for shdr in self.sections().iter().filter(|s| s.sh_type == sh_type) {
+            symbols.extend(self.symbols_in(shdr)?);
+        }
+        Ok(symbols)
+    }
+}
@@ -1,44 +1,116 @@
 use enum_primitive_derive::Primitive;
 use num_traits::FromPrimitive;
+use serde::Serialize;
 use std::convert::TryInto;
 use std::fmt;
+use std::io::{Cursor, Read};
 
-use crate::elf::helpers::{read_u8, read_u16, read_u32, read_u64};
+use crate::elf::error::ElfError;
+use crate::elf::helpers::{position, read_u8, read_u16, read_u32, read_u64};
 
-/// Indicate the OS and Application Binary Interface
-#[repr(u8)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
-pub enum EiOsabi{
-    ElfOsabiNONE       = 0,  
-    ElfOsabiHPUX       = 1, 
-    ElfOsabiNETBSD     = 2,
-    ElfOsabiLINUX      = 3,   
-    ElfOsabiAIX        = 7,  
-    ElfOsabiIRIX       = 8,  
-    ElfOsabiFREEBSD    = 9, 
-    ElfOsabiTRU64      = 10,  
-    ElfOsabiMODESTO    = 11,
-    ElfOsabiOPENBSD    = 12,
-    ElfOsabiOPENVMS    = 13,
-    ElfOsabiNSK        = 14,  
-    ElfOsabiARMAEABI   = 64,
-    ElfOsabiARM        = 97,
-    ElfOsabiSTANDALONE = 255,
-}
-
-/// Indicate version of ELF file
-#[repr(u8)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
-pub enum EiVersion {
-    // Invalid version
-    EvNone    = 0, 
-    // Actual version
-    EvCurrent = 1,
+/// Declares an enum for a field whose set of values is open ended. Values
+/// that are not listed are preserved either in one of the range variants
+/// (OS or processor specific values) or in `Unknown`, so converting from the
+/// raw value never fails.
+macro_rules! open_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident : $repr:ty {
+            $( $(#[$vmeta:meta])* $variant:ident = $value:literal, )*
+        }
+        $( ranges {
+            $( $(#[$rmeta:meta])* $range:ident = $lo:literal ..= $hi:literal, )*
+        } )?
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+        pub enum $name {
+            $( $(#[$vmeta])* $variant, )*
+            $( $( $(#[$rmeta])* $range($repr), )* )?
+            /// Value not defined by the specification
+            Unknown($repr),
+        }
+
+        impl From<$repr> for $name {
+            fn from(value : $repr) -> Self {
+                match value {
+                    $( $value => $name::$variant, )*
+                    $( $( $lo..=$hi => $name::$range(value), )* )?
+                    _ => $name::Unknown(value),
+                }
+            }
+        }
+
+        impl From<$name> for $repr {
+            fn from(value : $name) -> Self {
+                match value {
+                    $( $name::$variant => $value, )*
+                    $( $( $name::$range(v) => v, )* )?
+                    $name::Unknown(v) => v,
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S : serde::Serializer>(&self, serializer : S) 
+                -> Result<S::Ok, S::Error> 
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+                match *self {
+                    $( $( $name::$range(v) => 
+                        write!(f, "{}(0x{:x})", stringify!($range), v), )* )?
+                    $name::Unknown(v) => write!(f, "Unknown(0x{:x})", v),
+                    _ => write!(f, "{:?}", self),
+                }
+            }
+        }
+    }
+}
+
+open_enum! {
+    /// Indicate the OS and Application Binary Interface
+    pub enum EiOsabi : u8 {
+        ElfOsabiNONE       = 0,  
+        ElfOsabiHPUX       = 1, 
+        ElfOsabiNETBSD     = 2,
+        ElfOsabiLINUX      = 3,   
+        ElfOsabiSOLARIS    = 6,
+        ElfOsabiAIX        = 7,  
+        ElfOsabiIRIX       = 8,  
+        ElfOsabiFREEBSD    = 9, 
+        ElfOsabiTRU64      = 10,  
+        ElfOsabiMODESTO    = 11,
+        ElfOsabiOPENBSD    = 12,
+        ElfOsabiOPENVMS    = 13,
+        ElfOsabiNSK        = 14,  
+        ElfOsabiAROS       = 15,
+        ElfOsabiFENIXOS    = 16,
+        ElfOsabiCLOUDABI   = 17,
+        ElfOsabiOPENVOS    = 18,
+        ElfOsabiARMAEABI   = 64,
+        ElfOsabiARM        = 97,
+        ElfOsabiSTANDALONE = 255,
+    }
+}
+
+open_enum! {
+    /// Indicate version of ELF file
+    pub enum EiVersion : u8 {
+        /// Invalid version
+        EvNone    = 0, 
+        /// Actual version
+        EvCurrent = 1,
+    }
 }
 
 /// Indicate endiannes of ELF file
 #[repr(u8)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
+#[derive(Debug, PartialEq, Clone, Copy, Primitive, Serialize)]
 pub enum EiData {
     ElfDataNone = 0,
     ElfData2Lsb = 1,
@@ -47,7 +119,7 @@ pub enum EiData {
 
 /// Indicate the ELF architecture (x32 or x64)
 #[repr(u8)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
+#[derive(Debug, PartialEq, Clone, Primitive, Serialize)]
 pub enum EiClass {
     ElfClassNone = 0u8,
     ElfClass32   = 1u8,
@@ -59,8 +131,14 @@ impl fmt::Display for EiClass {
     }
 }
 
+impl fmt::Display for EiData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 /// Byte array that explains how to interpret the rest of the file 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EIdentStruct {
     pub magic :       [u8; 4],
     pub class :       EiClass,
@@ -84,67 +162,119 @@ impl Default for EIdentStruct {
     }
 }
 
-/// Indicate type of object file
-#[repr(u16)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
-pub enum EType {
-    EtNone = 0,
-    EtRel  = 1,
-    EtExec = 2,
-    EtDyn  = 3,
-    EtCore = 4,
+impl EIdentStruct {
+    /// Parse the `e_ident` array at the start of the file and check that its
+    /// class is the one expected by the caller
+    pub fn from_io(io : &mut Cursor<&[u8]>, expected : EiClass) 
+        -> Result<EIdentStruct, ElfError> 
+    {
+        let mut ident = EIdentStruct::default();
+
+        let offset = position(io);
+        let mut buf = [0; 16];
+        io.read_exact(&mut buf)
+            .map_err(|_| ElfError::Truncated { offset })?;
+
+        ident.magic = buf[0..4].try_into().unwrap();
+        if ident.magic != [0x7f, 0x45, 0x4c, 0x46] {
+            return Err(ElfError::BadMagic { offset, magic : ident.magic });
+        }
+        ident.class = match EiClass::from_u8(buf[4]) {
+            Some(v) if v == expected => v,
+            _ => return Err(ElfError::UnsupportedClass {
+                offset : offset + 4,
+                class  : buf[4],
+            }),
+        };
+        ident.endianness = match EiData::from_u8(buf[5]) {
+            Some(EiData::ElfDataNone) | None => {
+                return Err(ElfError::InvalidValue {
+                    offset : offset + 5,
+                    field  : "EI_DATA",
+                    value  : buf[5] as u64,
+                })
+            }
+            Some(v) => v,
+        };
+        ident.version = EiVersion::from(buf[6]);
+        ident.osabi = EiOsabi::from(buf[7]);
+        ident.abi_version = buf[8];
+
+        Ok(ident)
+    }
 }
 
-impl fmt::Display for EType {
-    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+open_enum! {
+    /// Indicate type of object file
+    pub enum EType : u16 {
+        EtNone = 0,
+        EtRel  = 1,
+        EtExec = 2,
+        EtDyn  = 3,
+        EtCore = 4,
+    }
+    ranges {
+        /// Operating system specific type
+        Os   = 0xfe00..=0xfeff,
+        /// Processor specific type
+        Proc = 0xff00..=0xffff,
     }
 }
 
-/// Indicate the required architecture for the file
-#[repr(u16)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
-pub enum EMachine {
-    EmNone        = 0,
-    EmM32         = 1,
-    EmSparc       = 2,
-    Em386         = 3,
-    Em68K         = 4,
-    Em88K         = 5,
-    Em860         = 7,
-    EmMips        = 8,
-    EmPAriscV     = 15,
-    EmSparc32Plus = 18, 
-    EmPPC         = 20,
-    EmPPC64       = 21,
-    EmS390        = 22,
-    EmARM         = 40,
-    EmSH          = 42,
-    EmSPARCv9     = 43,
-    EmIA64        = 50,
-    Emx86_64      = 62,
-    EmVax         = 75, 
-    EmRISCV       = 243,
-}
-
-impl fmt::Display for EMachine {
-    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+open_enum! {
+    /// Indicate the required architecture for the file
+    pub enum EMachine : u16 {
+        EmNone        = 0,
+        EmM32         = 1,
+        EmSparc       = 2,
+        Em386         = 3,
+        Em68K         = 4,
+        Em88K         = 5,
+        EmIAMCU       = 6,
+        Em860         = 7,
+        EmMips        = 8,
+        EmS370        = 9,
+        EmMipsRS3LE   = 10,
+        EmPAriscV     = 15,
+        EmSparc32Plus = 18, 
+        EmPPC         = 20,
+        EmPPC64       = 21,
+        EmS390        = 22,
+        EmSPU         = 23,
+        EmARM         = 40,
+        EmSH          = 42,
+        EmSPARCv9     = 43,
+        EmIA64        = 50,
+        Emx86_64      = 62,
+        EmVax         = 75, 
+        EmAVR         = 83,
+        EmXtensa      = 94,
+        EmMSP430      = 105,
+        EmTIC6000     = 140,
+        EmHexagon     = 164,
+        EmAArch64     = 183,
+        EmMicroBlaze  = 189,
+        EmCUDA        = 190,
+        EmAMDGPU      = 224,
+        EmRISCV       = 243,
+        EmBPF         = 247,
+        EmCSKY        = 252,
+        EmLoongArch   = 258,
     }
 }
 
 
-/// Header version 
-#[repr(u32)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
-pub enum EVersion {
-    EvNone    = 0u32,
-    EvCurrent = 1u32,
-    EvNum     = 2u32,
+open_enum! {
+    /// Header version 
+    pub enum EVersion : u32 {
+        EvNone    = 0,
+        EvCurrent = 1,
+        EvNum     = 2,
+    }
 }
 
 /// Elf header
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Elf64Ehdr {
     pub e_ident :      EIdentStruct,  // location of Segments/Sections Tables
     pub e_type :       EType,    // shared object, executable...
@@ -186,102 +316,68 @@ impl Default for Elf64Ehdr {
 
 impl Elf64Ehdr {
     /// Parse ELF Header 
-    pub fn from_io(mut io : &mut dyn std::io::Read) -> Option<Elf64Ehdr> {
+    // Filled field by field, in the order of the file
+    #[allow(clippy::field_reassign_with_default)]
+    pub fn from_io(io : &mut Cursor<&[u8]>) 
+        -> Result<Elf64Ehdr, ElfError> 
+    {
         
         let mut header = Elf64Ehdr::default();
 
-        let mut buf = [0; 16];
-        io.read_exact(&mut buf).expect("Cannot read io");
-
-        // Read the e_ident field in Elf64Ehdr
-        header.e_ident.magic = buf[0..4].try_into().unwrap();
-        assert!(header.e_ident.magic == [0x7f, 0x45, 0x4c, 0x46]);
-        header.e_ident.class = match EiClass::from_u8(buf[4]){
-            Some(v) => v,
-            None => panic!("e_indent class invalid"),
-        };
-        header.e_ident.endianness = match EiData::from_u8(buf[5]) {
-            Some(v) => v,
-            None => panic!("e_indent endianness invalid\n"),
-        };
-        header.e_ident.version = match EiVersion::from_u8(buf[6]) {
-            Some(v) => v, 
-            None => panic!("e_indent version invalid\n"),
-        };
-        header.e_ident.osabi = match EiOsabi::from_u8(buf[7]) {
-            Some(v) => v, 
-            None => panic!("e_indent OS ABI invalid\n"),
-        };
-        header.e_ident.abi_version = buf[8];
+        header.e_ident = EIdentStruct::from_io(io, EiClass::ElfClass64)?;
+        let endian = header.e_ident.endianness;
         
         // Read the other fields
-        header.e_type = match EType::from_u16(read_u16(&mut io)?) {
-            Some(v) => v,
-            None => panic!("e_type invalid\n"),
-        };
-
-        header.e_machine = match EMachine::from_u16(read_u16(&mut io)?) {
-            Some(v) => v,
-            None => panic!("e_machine invalid\n"),
-        };
+        header.e_type    = EType::from(read_u16(io, endian)?);
+        header.e_machine = EMachine::from(read_u16(io, endian)?);
+        header.e_version = EVersion::from(read_u32(io, endian)?);
 
-        header.e_version = match EVersion::from_u32(read_u32(&mut io)?) {
-            Some(v) => v,
-            None => panic!("e_version invalid\n"),
-        };
-
-        header.e_entry     = read_u64(&mut io)?;
-        header.e_phoff     = read_u64(&mut io)?;
-        header.e_shoff     = read_u64(&mut io)?;
-        header.e_flags     = read_u32(&mut io)?;
-        header.e_ehsize    = read_u16(&mut io)?;
-        header.e_phentsize = read_u16(&mut io)?;
-        header.e_phnum     = read_u16(&mut io)?;
-        header.e_shentsize = read_u16(&mut io)?;
-        header.e_shnum     = read_u16(&mut io)?;
-        header.e_shstrndx  = read_u16(&mut io)?;
+        header.e_entry     = read_u64(io, endian)?;
+        header.e_phoff     = read_u64(io, endian)?;
+        header.e_shoff     = read_u64(io, endian)?;
+        header.e_flags     = read_u32(io, endian)?;
+        header.e_ehsize    = read_u16(io, endian)?;
+        header.e_phentsize = read_u16(io, endian)?;
+        header.e_phnum     = read_u16(io, endian)?;
+        header.e_shentsize = read_u16(io, endian)?;
+        header.e_shnum     = read_u16(io, endian)?;
+        header.e_shstrndx  = read_u16(io, endian)?;
         
-        Some(header)
-    }
-}
-
-/// Indicates what kind of segment the Program Header describes
-#[repr(u32)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
-pub enum PType{
-    PtNull       = 0,
-    PtLoad       = 1,
-    PtDynamic    = 2,
-    PtInterp     = 3,
-    PtNote       = 4,
-    PtShlib      = 5,
-    PtPhdr       = 6,
-    PtTls        = 7,
-    PtLoos       = 0x60000000,
-    PtHios       = 0x6fffffff,
-    PtLoproc     = 0x70000000,
-    PtHiproc     = 0x7fffffff,
-    PtGnuEhFrame = 0x6474e550,
-    PtGnuStack   = 0x6474e551,
-    PtGnuRelro   = 0x6474e552,
-}
-
-impl fmt::Display for PType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        Ok(header)
     }
 }
 
-impl Default for PType {
-    fn default() -> Self {
-        PType::PtNull
+open_enum! {
+    /// Indicates what kind of segment the Program Header describes
+    #[derive(Default)]
+    pub enum PType : u32 {
+        #[default]
+        PtNull        = 0,
+        PtLoad        = 1,
+        PtDynamic     = 2,
+        PtInterp      = 3,
+        PtNote        = 4,
+        PtShlib       = 5,
+        PtPhdr        = 6,
+        PtTls         = 7,
+        PtGnuEhFrame  = 0x6474e550,
+        PtGnuStack    = 0x6474e551,
+        PtGnuRelro    = 0x6474e552,
+        PtGnuProperty = 0x6474e553,
+        PtGnuSframe   = 0x6474e554,
+    }
+    ranges {
+        /// Operating system specific segment
+        Os   = 0x60000000..=0x6fffffff,
+        /// Processor specific segment, e.g. `PT_ARM_EXIDX`
+        Proc = 0x70000000..=0x7fffffff,
     }
 }
 
 /// Program header
 /// Array of pub structures describing a segment or other information the 
 /// system needs to prepare the program for execution
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, Serialize)]
 pub struct Elf64Phdr {
     pub p_type   : PType, // u32, describes the type of the segment
     pub p_flags  : u32,   // R | W | X  
@@ -294,26 +390,27 @@ pub struct Elf64Phdr {
 }
 
 impl Elf64Phdr {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 56;
+
     /// Parse an entry in the program header table
-    pub fn from_io(mut io : &mut dyn std::io::Read) 
-        -> Option<Elf64Phdr> 
+    // Filled field by field, in the order of the file
+    #[allow(clippy::field_reassign_with_default)]
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf64Phdr, ElfError> 
     {
         let mut phdr = Elf64Phdr::default();   
-        let val = read_u32(&mut io)?;
-        phdr.p_type = match PType::from_u32(val) {
-            Some(v) => v,
-            None => panic!("PType in PHT parsing incorrect"),
-        };
+        phdr.p_type = PType::from(read_u32(io, endian)?);
 
-        phdr.p_flags  = read_u32(&mut io)?;
-        phdr.p_offset = read_u64(&mut io)?;
-        phdr.p_vaddr  = read_u64(&mut io)?;
-        phdr.p_paddr  = read_u64(&mut io)?;
-        phdr.p_filesz = read_u64(&mut io)?;
-        phdr.p_memsz  = read_u64(&mut io)?;
-        phdr.p_align  = read_u64(&mut io)?;
+        phdr.p_flags  = read_u32(io, endian)?;
+        phdr.p_offset = read_u64(io, endian)?;
+        phdr.p_vaddr  = read_u64(io, endian)?;
+        phdr.p_paddr  = read_u64(io, endian)?;
+        phdr.p_filesz = read_u64(io, endian)?;
+        phdr.p_memsz  = read_u64(io, endian)?;
+        phdr.p_align  = read_u64(io, endian)?;
 
-        Some(phdr)
+        Ok(phdr)
     }
     
     /// Check if there is a Read permission on this segment
@@ -328,67 +425,62 @@ impl Elf64Phdr {
 
     /// Check if there is an Exec permission on this segment
     pub fn has_x(&self) -> bool {
-        (self.p_flags >> 0) & 1 == 1
-    }
-}
-
-
-/// Categorize section content
-#[repr(u32)]
-#[derive(Debug, PartialEq, Clone, Primitive)]
-pub enum SHType {
-    ShtNULL         = 0,
-    ShtPROGBITS     = 1,
-    ShtSYMTAB       = 2,
-    ShtSTRTAB       = 3,
-    ShtRELA         = 4,
-    ShtHASH         = 5,
-    ShtDYNAMIC      = 6,
-    ShtNOTE         = 7,
-    ShtNOBITS       = 8,
-    ShtREL          = 9,
-    ShtSHLIB        = 10,
-    ShtDYNSYM       = 11,
-    ShtINITARRAY    = 14,
-    ShtFINIARRAY    = 15,
-    ShtPREINITARRAY = 16,
-    ShtGROUP        = 17,
-    ShtSYMTABSHNDX  = 18,
-    ShtNUM          = 19,
-    ShtLOOS         = 0x60000000,
-    ShtGNUAttr      = 0x6ffffff5,
-    ShtGnuHash      = 0x6ffffff6,
-    ShtGnuLiblist   = 0x6ffffff7,
-    ShtChecksum     = 0x6ffffff8,
-    ShtLosunw       = 0x6ffffffa,
-    ShtSunwComdat   = 0x6ffffffb,
-    ShtSunwSyminfo  = 0x6ffffffc,
-    ShtGnuVerdef    = 0x6ffffffd,
-    ShtGnuVerneed   = 0x6ffffffe,
-    ShtGnuVersym    = 0x6fffffff,
-    ShtLOPROC       = 0x70000000,
-    ShtHIPROC       = 0x7fffffff,
-    ShtLOUSER       = 0x80000000u32,
-    ShtHIUSER       = 0xffffffffu32,
-}
-
-impl fmt::Display for SHType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        self.p_flags & 1 == 1
     }
 }
 
-impl Default for SHType {
-    fn default() -> Self {
-        SHType::ShtNULL
+
+open_enum! {
+    /// Categorize section content
+    #[derive(Default)]
+    pub enum SHType : u32 {
+        #[default]
+        ShtNULL         = 0,
+        ShtPROGBITS     = 1,
+        ShtSYMTAB       = 2,
+        ShtSTRTAB       = 3,
+        ShtRELA         = 4,
+        ShtHASH         = 5,
+        ShtDYNAMIC      = 6,
+        ShtNOTE         = 7,
+        ShtNOBITS       = 8,
+        ShtREL          = 9,
+        ShtSHLIB        = 10,
+        ShtDYNSYM       = 11,
+        ShtINITARRAY    = 14,
+        ShtFINIARRAY    = 15,
+        ShtPREINITARRAY = 16,
+        ShtGROUP        = 17,
+        ShtSYMTABSHNDX  = 18,
+        ShtRELR         = 19,
+        ShtLlvmAddrsig  = 0x6fff4c03,
+        ShtGNUAttr      = 0x6ffffff5,
+        ShtGnuHash      = 0x6ffffff6,
+        ShtGnuLiblist   = 0x6ffffff7,
+        ShtChecksum     = 0x6ffffff8,
+        ShtLosunw       = 0x6ffffffa,
+        ShtSunwComdat   = 0x6ffffffb,
+        ShtSunwSyminfo  = 0x6ffffffc,
+        ShtGnuVerdef    = 0x6ffffffd,
+        ShtGnuVerneed   = 0x6ffffffe,
+        ShtGnuVersym    = 0x6fffffff,
+    }
+    ranges {
+        /// Operating system specific section
+        Os   = 0x60000000..=0x6fffffff,
+        /// Processor specific section, e.g. `SHT_ARM_EXIDX`
+        Proc = 0x70000000..=0x7fffffff,
+        /// Section reserved for applications
+        User = 0x80000000..=0xffffffff,
     }
 }
 
 /// Flags that describes misc attributes for a section
 #[repr(u64)]
-#[derive(Primitive)]
+#[derive(Default, Primitive)]
 pub enum SHFlags {
     ShfNull          = 0,
+    #[default]
     ShfWrite         = 1,
     ShfAlloc         = 2,
     ShfExecInstr     = 4,
@@ -398,14 +490,8 @@ pub enum SHFlags {
     ShfMaskProc      = 0xf0000000u64,
 }
 
-impl Default for SHFlags {
-    fn default() -> Self {
-        SHFlags::ShfWrite
-    }
-}
-
 /// Describe a section of the ELF file
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct Elf64Shdr {
     pub sh_name      : u32,     // Index into section header string table, 
                                 // gives name location
@@ -419,64 +505,151 @@ pub struct Elf64Shdr {
     pub sh_info      : u32,     // Holds extra info
     pub sh_addralign : u64,     // Alignment for the section
     pub sh_entsize   : u64,     // Size of an entry in the section table if it has one
+    pub name         : String,  // Name resolved through the section header 
+                                // string table
 }
 
 impl Elf64Shdr {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 64;
+
     /// Parse an entry in the Section Header Table
-    pub fn from_io(mut io: &mut dyn std::io::Read) 
-        -> Option<Elf64Shdr> 
+    // Filled field by field, in the order of the file
+    #[allow(clippy::field_reassign_with_default)]
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf64Shdr, ElfError> 
     {
         let mut shdr = Elf64Shdr::default();
         
-        shdr.sh_name = read_u32(&mut io)?;
-        let val = read_u32(&mut io)?;
-        shdr.sh_type = match SHType::from_u32(val) {
-            Some(v) => v,
-            None => panic!("shentry type invalid"),
-        };
-        shdr.sh_flags     = read_u64(&mut io)?;
-        shdr.sh_addr      = read_u64(&mut io)?;
-        shdr.sh_offset    = read_u64(&mut io)?;
-        shdr.sh_size      = read_u64(&mut io)?;
-        shdr.sh_link      = read_u32(&mut io)?;
-        shdr.sh_info      = read_u32(&mut io)?;
-        shdr.sh_addralign = read_u64(&mut io)?;
-        shdr.sh_entsize   = read_u64(&mut io)?;
+        shdr.sh_name = read_u32(io, endian)?;
+        shdr.sh_type = SHType::from(read_u32(io, endian)?);
+        shdr.sh_flags     = read_u64(io, endian)?;
+        shdr.sh_addr      = read_u64(io, endian)?;
+        shdr.sh_offset    = read_u64(io, endian)?;
+        shdr.sh_size      = read_u64(io, endian)?;
+        shdr.sh_link      = read_u32(io, endian)?;
+        shdr.sh_info      = read_u32(io, endian)?;
+        shdr.sh_addralign = read_u64(io, endian)?;
+        shdr.sh_entsize   = read_u64(io, endian)?;
         
-        Some(shdr)
+        Ok(shdr)
+    }
+}
+
+/// Undefined section index, the symbol is imported
+pub const SHN_UNDEF  : u16 = 0;
+/// The symbol has an absolute value that will not change with relocations
+pub const SHN_ABS    : u16 = 0xfff1;
+/// The symbol labels a common block that has not yet been allocated
+pub const SHN_COMMON : u16 = 0xfff2;
+/// The section index is held in the `SHT_SYMTAB_SHNDX` section
+pub const SHN_XINDEX : u16 = 0xffff;
+
+/// The section holds data writable during execution
+pub const SHF_WRITE     : u64 = 0x1;
+/// The section occupies memory during execution
+pub const SHF_ALLOC     : u64 = 0x2;
+/// The section holds executable instructions
+pub const SHF_EXECINSTR : u64 = 0x4;
+
+open_enum! {
+    /// Symbol binding, stored in the upper 4 bits of `st_info`
+    pub enum SymBind : u8 {
+        StbLocal     = 0,
+        StbGlobal    = 1,
+        StbWeak      = 2,
+        StbGnuUnique = 10,
+    }
+    ranges {
+        /// Operating system specific binding
+        Os   = 10..=12,
+        /// Processor specific binding
+        Proc = 13..=15,
+    }
+}
+
+open_enum! {
+    /// Symbol type, stored in the lower 4 bits of `st_info`
+    pub enum SymType : u8 {
+        SttNotype    = 0,
+        SttObject    = 1,
+        SttFunc      = 2,
+        SttSection   = 3,
+        SttFile      = 4,
+        SttCommon    = 5,
+        SttTls       = 6,
+        SttGnuIfunc  = 10,
+    }
+    ranges {
+        /// Operating system specific type
+        Os   = 10..=12,
+        /// Processor specific type
+        Proc = 13..=15,
+    }
+}
+
+open_enum! {
+    /// Symbol visibility, stored in the lower 2 bits of `st_other`
+    pub enum SymVisibility : u8 {
+        StvDefault   = 0,
+        StvInternal  = 1,
+        StvHidden    = 2,
+        StvProtected = 3,
     }
 }
 
 /// An entry in the symbol table
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Elf64Sym {
-    st_name  : u32,     // index into file's symbol string table
-    st_info  : u8,      // symbol's type
-    st_other : u8,      // symbol's visibility
-    st_shndx : u16,     // section header table index
-    st_value : u64,     // value of symbol
-    st_size  : u64,     // size of symbol
+    pub st_name  : u32,     // index into file's symbol string table
+    pub st_info  : u8,      // symbol's type
+    pub st_other : u8,      // symbol's visibility
+    pub st_shndx : u16,     // section header table index
+    pub st_value : u64,     // value of symbol
+    pub st_size  : u64,     // size of symbol
 }
 
 impl Elf64Sym {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 24;
+
     /// Parse an entry in the symbol table
-    pub fn from_io(mut io : &mut dyn std::io::Read) -> Option<Elf64Sym> {
+    // Filled field by field, in the order of the file
+    #[allow(clippy::field_reassign_with_default)]
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf64Sym, ElfError> 
+    {
         let mut entry = Elf64Sym::default();
         
-        entry.st_name  = read_u32(&mut io)?;
-        entry.st_info  = read_u8(&mut io)?;
-        entry.st_other = read_u8(&mut io)?;
-        entry.st_shndx = read_u16(&mut io)?;
-        entry.st_value = read_u64(&mut io)?;
-        entry.st_size  = read_u64(&mut io)?;
+        entry.st_name  = read_u32(io, endian)?;
+        entry.st_info  = read_u8(io, endian)?;
+        entry.st_other = read_u8(io, endian)?;
+        entry.st_shndx = read_u16(io, endian)?;
+        entry.st_value = read_u64(io, endian)?;
+        entry.st_size  = read_u64(io, endian)?;
+
+        Ok(entry)
 
-        Some(entry)
+    }
 
+    /// Binding of the symbol
+    pub fn bind(&self) -> SymBind {
+        SymBind::from(self.st_info >> 4)
+    }
+
+    /// Type of the symbol
+    pub fn sym_type(&self) -> SymType {
+        SymType::from(self.st_info & 0xf)
+    }
+
+    /// Visibility of the symbol
+    pub fn visibility(&self) -> SymVisibility {
+        SymVisibility::from(self.st_other & 0x3)
     }
 }
 
 /// Elf32 Header
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Elf32Ehdr {
     pub e_ident :      EIdentStruct,  // location of Segments/Sections Tables
     pub e_type :       EType,    // shared object, executable...
@@ -515,71 +688,65 @@ impl Default for Elf32Ehdr {
     }
 }
 
+/// Widen a 32 bits header to the 64 bits layout
+impl From<Elf32Ehdr> for Elf64Ehdr {
+    fn from(ehdr : Elf32Ehdr) -> Self {
+        Elf64Ehdr {
+            e_ident     : ehdr.e_ident,
+            e_type      : ehdr.e_type,
+            e_machine   : ehdr.e_machine,
+            e_version   : ehdr.e_version,
+            e_entry     : ehdr.e_entry as u64,
+            e_phoff     : ehdr.e_phoff as u64,
+            e_shoff     : ehdr.e_shoff as u64,
+            e_flags     : ehdr.e_flags,
+            e_ehsize    : ehdr.e_ehsize,
+            e_phentsize : ehdr.e_phentsize,
+            e_phnum     : ehdr.e_phnum,
+            e_shentsize : ehdr.e_shentsize,
+            e_shnum     : ehdr.e_shnum,
+            e_shstrndx  : ehdr.e_shstrndx,
+        }
+    }
+}
+
 impl Elf32Ehdr {
     /// Parse ELF32 Header 
-    pub fn from_io(mut io : &mut dyn std::io::Read) -> Option<Elf32Ehdr> {
+    // Filled field by field, in the order of the file
+    #[allow(clippy::field_reassign_with_default)]
+    pub fn from_io(io : &mut Cursor<&[u8]>) 
+        -> Result<Elf32Ehdr, ElfError> 
+    {
         
         let mut header = Elf32Ehdr::default();
 
-        let mut buf = [0; 16];
-        io.read_exact(&mut buf).expect("Cannot read io");
-
-        // Read the e_ident field in Elf32Ehdr
-        header.e_ident.magic = buf[0..4].try_into().unwrap();
-        assert!(header.e_ident.magic == [0x7f, 0x45, 0x4c, 0x46]);
-        header.e_ident.class = match EiClass::from_u8(buf[4]){
-            Some(v) => v,
-            None => panic!("e_indent class invalid"),
-        };
-        header.e_ident.endianness = match EiData::from_u8(buf[5]) {
-            Some(v) => v,
-            None => panic!("e_indent endianness invalid\n"),
-        };
-        header.e_ident.version = match EiVersion::from_u8(buf[6]) {
-            Some(v) => v, 
-            None => panic!("e_indent version invalid\n"),
-        };
-        header.e_ident.osabi = match EiOsabi::from_u8(buf[7]) {
-            Some(v) => v, 
-            None => panic!("e_indent OS ABI invalid\n"),
-        };
-        header.e_ident.abi_version = buf[8];
+        header.e_ident = EIdentStruct::from_io(io, EiClass::ElfClass32)?;
+        let endian = header.e_ident.endianness;
         
         // Read the other fields
-        header.e_type = match EType::from_u16(read_u16(&mut io)?) {
-            Some(v) => v,
-            None => panic!("e_type invalid\n"),
-        };
-
-        header.e_machine = match EMachine::from_u16(read_u16(&mut io)?) {
-            Some(v) => v,
-            None => panic!("e_machine invalid\n"),
-        };
+        header.e_type    = EType::from(read_u16(io, endian)?);
+        header.e_machine = EMachine::from(read_u16(io, endian)?);
+        header.e_version = EVersion::from(read_u32(io, endian)?);
 
-        header.e_version = match EVersion::from_u32(read_u32(&mut io)?) {
-            Some(v) => v,
-            None => panic!("e_version invalid\n"),
-        };
-
-        header.e_entry     = read_u32(&mut io)?;
-        header.e_phoff     = read_u32(&mut io)?;
-        header.e_shoff     = read_u32(&mut io)?;
-        header.e_flags     = read_u32(&mut io)?;
-        header.e_ehsize    = read_u16(&mut io)?;
-        header.e_phentsize = read_u16(&mut io)?;
-        header.e_phnum     = read_u16(&mut io)?;
-        header.e_shentsize = read_u16(&mut io)?;
-        header.e_shnum     = read_u16(&mut io)?;
-        header.e_shstrndx  = read_u16(&mut io)?;
+        header.e_entry     = read_u32(io, endian)?;
+        header.e_phoff     = read_u32(io, endian)?;
+        header.e_shoff     = read_u32(io, endian)?;
+        header.e_flags     = read_u32(io, endian)?;
+        header.e_ehsize    = read_u16(io, endian)?;
+        header.e_phentsize = read_u16(io, endian)?;
+        header.e_phnum     = read_u16(io, endian)?;
+        header.e_shentsize = read_u16(io, endian)?;
+        header.e_shnum     = read_u16(io, endian)?;
+        header.e_shstrndx  = read_u16(io, endian)?;
         
-        Some(header)
+        Ok(header)
     }
 }
 
 /// Program header 32 bits
 /// Array of pub structures describing a segment or other information the 
 /// system needs to prepare the program for execution
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Elf32Phdr {
     pub p_type   : PType, // u32, describes the type of the segment
     pub p_offset : u32,   // Offset of the segment
@@ -592,26 +759,27 @@ pub struct Elf32Phdr {
 }
 
 impl Elf32Phdr {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 32;
+
     /// Parse an entry in the program header table
-    pub fn from_io(mut io : &mut dyn std::io::Read) 
-        -> Option<Elf32Phdr> 
+    // Filled field by field, in the order of the file
+    #[allow(clippy::field_reassign_with_default)]
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf32Phdr, ElfError> 
     {
         let mut phdr = Elf32Phdr::default();   
-        let val = read_u32(&mut io)?;
-        phdr.p_type = match PType::from_u32(val) {
-            Some(v) => v,
-            None => panic!("PType in PHT parsing incorrect"),
-        };
+        phdr.p_type = PType::from(read_u32(io, endian)?);
 
-        phdr.p_offset = read_u32(&mut io)?;
-        phdr.p_vaddr  = read_u32(&mut io)?;
-        phdr.p_paddr  = read_u32(&mut io)?;
-        phdr.p_filesz = read_u32(&mut io)?;
-        phdr.p_memsz  = read_u32(&mut io)?;
-        phdr.p_flags  = read_u32(&mut io)?;
-        phdr.p_align  = read_u32(&mut io)?;
+        phdr.p_offset = read_u32(io, endian)?;
+        phdr.p_vaddr  = read_u32(io, endian)?;
+        phdr.p_paddr  = read_u32(io, endian)?;
+        phdr.p_filesz = read_u32(io, endian)?;
+        phdr.p_memsz  = read_u32(io, endian)?;
+        phdr.p_flags  = read_u32(io, endian)?;
+        phdr.p_align  = read_u32(io, endian)?;
 
-        Some(phdr)
+        Ok(phdr)
     }
     
     /// Check if there is a Read permission on this segment
@@ -626,7 +794,23 @@ impl Elf32Phdr {
 
     /// Check if there is an Exec permission on this segment
     pub fn has_x(&self) -> bool {
-        (self.p_flags >> 0) & 1 == 1
+        self.p_flags & 1 == 1
+    }
+}
+
+/// Widen a 32 bits program header to the 64 bits layout
+impl From<Elf32Phdr> for Elf64Phdr {
+    fn from(phdr : Elf32Phdr) -> Self {
+        Elf64Phdr {
+            p_type   : phdr.p_type,
+            p_flags  : phdr.p_flags,
+            p_offset : phdr.p_offset as u64,
+            p_vaddr  : phdr.p_vaddr as u64,
+            p_paddr  : phdr.p_paddr as u64,
+            p_filesz : phdr.p_filesz as u64,
+            p_memsz  : phdr.p_memsz as u64,
+            p_align  : phdr.p_align as u64,
+        }
     }
 }
 
@@ -645,31 +829,540 @@ pub struct Elf32Shdr {
     pub sh_info      : u32,     // Holds extra info
     pub sh_addralign : u32,     // Alignment for the section
     pub sh_entsize   : u32,     // Size of an entry in the section table if it has one
+    pub name         : String,  // Name resolved through the section header 
+                                // string table
 }
 
 impl Elf32Shdr {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 40;
+
     /// Parse an entry in the Section Header Table
-    pub fn from_io(mut io: &mut dyn std::io::Read) 
-        -> Option<Elf32Shdr> 
+    // Filled field by field, in the order of the file
+    #[allow(clippy::field_reassign_with_default)]
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf32Shdr, ElfError> 
     {
         let mut shdr = Elf32Shdr::default();
         
-        shdr.sh_name = read_u32(&mut io)?;
-        let val = read_u32(&mut io)?;
-        shdr.sh_type = match SHType::from_u32(val) {
-            Some(v) => v,
-            None => panic!("shentry type invalid"),
-        };
-        shdr.sh_flags     = read_u32(&mut io)?;
-        shdr.sh_addr      = read_u32(&mut io)?;
-        shdr.sh_offset    = read_u32(&mut io)?;
-        shdr.sh_size      = read_u32(&mut io)?;
-        shdr.sh_link      = read_u32(&mut io)?;
-        shdr.sh_info      = read_u32(&mut io)?;
-        shdr.sh_addralign = read_u32(&mut io)?;
-        shdr.sh_entsize   = read_u32(&mut io)?;
+        shdr.sh_name = read_u32(io, endian)?;
+        shdr.sh_type = SHType::from(read_u32(io, endian)?);
+        shdr.sh_flags     = read_u32(io, endian)?;
+        shdr.sh_addr      = read_u32(io, endian)?;
+        shdr.sh_offset    = read_u32(io, endian)?;
+        shdr.sh_size      = read_u32(io, endian)?;
+        shdr.sh_link      = read_u32(io, endian)?;
+        shdr.sh_info      = read_u32(io, endian)?;
+        shdr.sh_addralign = read_u32(io, endian)?;
+        shdr.sh_entsize   = read_u32(io, endian)?;
         
-        Some(shdr)
+        Ok(shdr)
+    }
+}
+
+/// Widen a 32 bits section header to the 64 bits layout
+impl From<Elf32Shdr> for Elf64Shdr {
+    fn from(shdr : Elf32Shdr) -> Self {
+        Elf64Shdr {
+            sh_name      : shdr.sh_name,
+            sh_type      : shdr.sh_type,
+            sh_flags     : shdr.sh_flags as u64,
+            sh_addr      : shdr.sh_addr as u64,
+            sh_offset    : shdr.sh_offset as u64,
+            sh_size      : shdr.sh_size as u64,
+            sh_link      : shdr.sh_link,
+            sh_info      : shdr.sh_info,
+            sh_addralign : shdr.sh_addralign as u64,
+            sh_entsize   : shdr.sh_entsize as u64,
+            name         : shdr.name,
+        }
     }
 }
 
+/// An entry in the symbol table 32 bits
+#[derive(Default, Debug, Clone)]
+pub struct Elf32Sym {
+    pub st_name  : u32,     // index into file's symbol string table
+    pub st_value : u32,     // value of symbol
+    pub st_size  : u32,     // size of symbol
+    pub st_info  : u8,      // symbol's type
+    pub st_other : u8,      // symbol's visibility
+    pub st_shndx : u16,     // section header table index
+}
+
+impl Elf32Sym {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 16;
+
+    /// Parse an entry in the symbol table
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf32Sym, ElfError> 
+    {
+        Ok(Elf32Sym {
+            st_name  : read_u32(io, endian)?,
+            st_value : read_u32(io, endian)?,
+            st_size  : read_u32(io, endian)?,
+            st_info  : read_u8(io, endian)?,
+            st_other : read_u8(io, endian)?,
+            st_shndx : read_u16(io, endian)?,
+        })
+    }
+}
+
+/// Widen a 32 bits symbol to the 64 bits layout
+impl From<Elf32Sym> for Elf64Sym {
+    fn from(sym : Elf32Sym) -> Self {
+        Elf64Sym {
+            st_name  : sym.st_name,
+            st_info  : sym.st_info,
+            st_other : sym.st_other,
+            st_shndx : sym.st_shndx,
+            st_value : sym.st_value as u64,
+            st_size  : sym.st_size as u64,
+        }
+    }
+}
+
+open_enum! {
+    /// Tag of an entry of the dynamic array, giving the meaning of its value
+    pub enum DynTag : i64 {
+        DtNull           = 0,
+        DtNeeded         = 1,
+        DtPltRelSz       = 2,
+        DtPltGot         = 3,
+        DtHash           = 4,
+        DtStrTab         = 5,
+        DtSymTab         = 6,
+        DtRela           = 7,
+        DtRelaSz         = 8,
+        DtRelaEnt        = 9,
+        DtStrSz          = 10,
+        DtSymEnt         = 11,
+        DtInit           = 12,
+        DtFini           = 13,
+        DtSoname         = 14,
+        DtRpath          = 15,
+        DtSymbolic       = 16,
+        DtRel            = 17,
+        DtRelSz          = 18,
+        DtRelEnt         = 19,
+        DtPltRel         = 20,
+        DtDebug          = 21,
+        DtTextRel        = 22,
+        DtJmpRel         = 23,
+        DtBindNow        = 24,
+        DtInitArray      = 25,
+        DtFiniArray      = 26,
+        DtInitArraySz    = 27,
+        DtFiniArraySz    = 28,
+        DtRunpath        = 29,
+        DtFlags          = 30,
+        DtPreinitArray   = 32,
+        DtPreinitArraySz = 33,
+        DtSymTabShndx    = 34,
+        DtRelrSz         = 35,
+        DtRelr           = 36,
+        DtRelrEnt        = 37,
+        DtGnuPrelinked   = 0x6ffffdf5,
+        DtChecksum       = 0x6ffffdf8,
+        DtGnuHash        = 0x6ffffef5,
+        DtTlsDescPlt     = 0x6ffffef6,
+        DtTlsDescGot     = 0x6ffffef7,
+        DtConfig         = 0x6ffffefa,
+        DtDepAudit       = 0x6ffffefb,
+        DtAudit          = 0x6ffffefc,
+        DtVerSym         = 0x6ffffff0,
+        DtRelaCount      = 0x6ffffff9,
+        DtRelCount       = 0x6ffffffa,
+        DtFlags1         = 0x6ffffffb,
+        DtVerDef         = 0x6ffffffc,
+        DtVerDefNum      = 0x6ffffffd,
+        DtVerNeed        = 0x6ffffffe,
+        DtVerNeedNum     = 0x6fffffff,
+        DtAuxiliary      = 0x7ffffffd,
+        DtFilter         = 0x7fffffff,
+    }
+    ranges {
+        /// Operating system specific tag
+        Os   = 0x60000000..=0x6fffffff,
+        /// Processor specific tag
+        Proc = 0x70000000..=0x7fffffff,
+    }
+}
+
+/// `DT_FLAGS`: the object may reference `$ORIGIN`
+pub const DF_ORIGIN     : u64 = 0x1;
+/// `DT_FLAGS`: symbol resolution starts from the object itself
+pub const DF_SYMBOLIC   : u64 = 0x2;
+/// `DT_FLAGS`: relocations may modify a non-writable segment
+pub const DF_TEXTREL    : u64 = 0x4;
+/// `DT_FLAGS`: all relocations must be processed at load time
+pub const DF_BIND_NOW   : u64 = 0x8;
+/// `DT_FLAGS`: the object uses the static TLS model
+pub const DF_STATIC_TLS : u64 = 0x10;
+
+/// `DT_FLAGS_1`: all relocations must be processed at load time
+pub const DF_1_NOW      : u64 = 0x1;
+/// `DT_FLAGS_1`: the object cannot be unloaded
+pub const DF_1_NODELETE : u64 = 0x8;
+/// `DT_FLAGS_1`: the object may not be opened with `dlopen`
+pub const DF_1_NOOPEN   : u64 = 0x40;
+/// `DT_FLAGS_1`: the object may reference `$ORIGIN`
+pub const DF_1_ORIGIN   : u64 = 0x80;
+/// `DT_FLAGS_1`: the object is a position independent executable
+pub const DF_1_PIE      : u64 = 0x08000000;
+
+/// An entry of the dynamic array
+#[derive(Default, Debug, Clone)]
+pub struct Elf64Dyn {
+    pub d_tag : i64,    // type of the entry
+    pub d_val : u64,    // integer value or address
+}
+
+impl Elf64Dyn {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 16;
+
+    /// Parse an entry in the dynamic array
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf64Dyn, ElfError> 
+    {
+        Ok(Elf64Dyn {
+            d_tag : read_u64(io, endian)? as i64,
+            d_val : read_u64(io, endian)?,
+        })
+    }
+}
+
+/// An entry of the dynamic array 32 bits
+#[derive(Default, Debug, Clone)]
+pub struct Elf32Dyn {
+    pub d_tag : i32,    // type of the entry
+    pub d_val : u32,    // integer value or address
+}
+
+impl Elf32Dyn {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 8;
+
+    /// Parse an entry in the dynamic array
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf32Dyn, ElfError> 
+    {
+        Ok(Elf32Dyn {
+            d_tag : read_u32(io, endian)? as i32,
+            d_val : read_u32(io, endian)?,
+        })
+    }
+}
+
+/// Widen a 32 bits dynamic entry to the 64 bits layout
+impl From<Elf32Dyn> for Elf64Dyn {
+    fn from(entry : Elf32Dyn) -> Self {
+        Elf64Dyn {
+            d_tag : entry.d_tag as i64,
+            d_val : entry.d_val as u64,
+        }
+    }
+}
+
+/// A relocation entry without addend
+#[derive(Default, Debug, Clone)]
+pub struct Elf64Rel {
+    pub r_offset : u64,     // location to apply the relocation to
+    pub r_info   : u64,     // symbol index and type of relocation
+}
+
+impl Elf64Rel {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 16;
+
+    /// Parse a relocation entry
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf64Rel, ElfError> 
+    {
+        Ok(Elf64Rel {
+            r_offset : read_u64(io, endian)?,
+            r_info   : read_u64(io, endian)?,
+        })
+    }
+}
+
+/// A relocation entry with an explicit addend
+#[derive(Default, Debug, Clone)]
+pub struct Elf64Rela {
+    pub r_offset : u64,     // location to apply the relocation to
+    pub r_info   : u64,     // symbol index and type of relocation
+    pub r_addend : i64,     // constant addend used to compute the value
+}
+
+impl Elf64Rela {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 24;
+
+    /// Parse a relocation entry
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf64Rela, ElfError> 
+    {
+        Ok(Elf64Rela {
+            r_offset : read_u64(io, endian)?,
+            r_info   : read_u64(io, endian)?,
+            r_addend : read_u64(io, endian)? as i64,
+        })
+    }
+}
+
+/// A relocation entry without addend 32 bits
+#[derive(Default, Debug, Clone)]
+pub struct Elf32Rel {
+    pub r_offset : u32,     // location to apply the relocation to
+    pub r_info   : u32,     // symbol index and type of relocation
+}
+
+impl Elf32Rel {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 8;
+
+    /// Parse a relocation entry
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf32Rel, ElfError> 
+    {
+        Ok(Elf32Rel {
+            r_offset : read_u32(io, endian)?,
+            r_info   : read_u32(io, endian)?,
+        })
+    }
+}
+
+/// A relocation entry with an explicit addend 32 bits
+#[derive(Default, Debug, Clone)]
+pub struct Elf32Rela {
+    pub r_offset : u32,     // location to apply the relocation to
+    pub r_info   : u32,     // symbol index and type of relocation
+    pub r_addend : i32,     // constant addend used to compute the value
+}
+
+impl Elf32Rela {
+    /// Size of an entry in the file
+    pub const SIZE : u64 = 12;
+
+    /// Parse a relocation entry
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<Elf32Rela, ElfError> 
+    {
+        Ok(Elf32Rela {
+            r_offset : read_u32(io, endian)?,
+            r_info   : read_u32(io, endian)?,
+            r_addend : read_u32(io, endian)? as i32,
+        })
+    }
+}
+
+/// Header of a note, identical for both classes
+#[derive(Default, Debug, Clone)]
+pub struct ElfNhdr {
+    pub n_namesz : u32,     // size of the owner name, including the NUL
+    pub n_descsz : u32,     // size of the descriptor
+    pub n_type   : u32,     // type of the note, interpreted per owner
+}
+
+impl ElfNhdr {
+    /// Size of a note header in the file
+    pub const SIZE : u64 = 12;
+
+    /// Parse a note header
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<ElfNhdr, ElfError> 
+    {
+        Ok(ElfNhdr {
+            n_namesz : read_u32(io, endian)?,
+            n_descsz : read_u32(io, endian)?,
+            n_type   : read_u32(io, endian)?,
+        })
+    }
+}
+
+/// `GNU` note: ABI the file is built for
+pub const NT_GNU_ABI_TAG         : u32 = 1;
+/// `GNU` note: unique identifier of the build
+pub const NT_GNU_BUILD_ID        : u32 = 3;
+/// `GNU` note: version of the gold linker that produced the file
+pub const NT_GNU_GOLD_VERSION    : u32 = 4;
+/// `GNU` note: array of program properties
+pub const NT_GNU_PROPERTY_TYPE_0 : u32 = 5;
+
+/// `NT_GNU_ABI_TAG` operating systems
+pub const GNU_ABI_TAG_LINUX    : u32 = 0;
+pub const GNU_ABI_TAG_HURD     : u32 = 1;
+pub const GNU_ABI_TAG_SOLARIS  : u32 = 2;
+pub const GNU_ABI_TAG_FREEBSD  : u32 = 3;
+pub const GNU_ABI_TAG_NETBSD   : u32 = 4;
+pub const GNU_ABI_TAG_SYLLABLE : u32 = 5;
+
+/// Property: size of the stack required by the program
+pub const GNU_PROPERTY_STACK_SIZE            : u32 = 1;
+/// Property: no copy relocation on protected data symbols
+pub const GNU_PROPERTY_NO_COPY_ON_PROTECTED  : u32 = 2;
+/// Property: x86 features that every input object supports
+pub const GNU_PROPERTY_X86_FEATURE_1_AND     : u32 = 0xc0000002;
+/// Property: x86 ISA level required by the program
+pub const GNU_PROPERTY_X86_ISA_1_NEEDED      : u32 = 0xc0008002;
+/// Property: AArch64 features that every input object supports
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_AND : u32 = 0xc0000000;
+
+/// `GNU_PROPERTY_X86_FEATURE_1_AND`: indirect branch tracking (IBT)
+pub const GNU_PROPERTY_X86_FEATURE_1_IBT   : u32 = 0x1;
+/// `GNU_PROPERTY_X86_FEATURE_1_AND`: shadow stack (SHSTK)
+pub const GNU_PROPERTY_X86_FEATURE_1_SHSTK : u32 = 0x2;
+
+/// `GNU_PROPERTY_AARCH64_FEATURE_1_AND`: branch target identification
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_BTI : u32 = 0x1;
+/// `GNU_PROPERTY_AARCH64_FEATURE_1_AND`: pointer authentication
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_PAC : u32 = 0x2;
+
+/// `.gnu.version` index of local symbols
+pub const VER_NDX_LOCAL  : u16 = 0;
+/// `.gnu.version` index of unversioned global symbols
+pub const VER_NDX_GLOBAL : u16 = 1;
+/// `.gnu.version` bit marking a version that is not the default one
+pub const VERSYM_HIDDEN  : u16 = 0x8000;
+
+/// Version definition of the file itself, named after its soname
+pub const VER_FLG_BASE : u16 = 0x1;
+/// Weak version requirement
+pub const VER_FLG_WEAK : u16 = 0x2;
+
+/// Entry of `.gnu.version_d`, identical for both classes
+#[derive(Default, Debug, Clone)]
+pub struct ElfVerdef {
+    pub vd_version : u16,   // revision of the structure, 1
+    pub vd_flags   : u16,   // VER_FLG_*
+    pub vd_ndx     : u16,   // index referenced by `.gnu.version`
+    pub vd_cnt     : u16,   // number of auxiliary entries
+    pub vd_hash    : u32,   // SysV hash of the version name
+    pub vd_aux     : u32,   // offset of the first auxiliary entry
+    pub vd_next    : u32,   // offset of the next definition, 0 for the last
+}
+
+impl ElfVerdef {
+    /// Size of a version definition in the file
+    pub const SIZE : u64 = 20;
+
+    /// Parse a version definition
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<ElfVerdef, ElfError> 
+    {
+        Ok(ElfVerdef {
+            vd_version : read_u16(io, endian)?,
+            vd_flags   : read_u16(io, endian)?,
+            vd_ndx     : read_u16(io, endian)?,
+            vd_cnt     : read_u16(io, endian)?,
+            vd_hash    : read_u32(io, endian)?,
+            vd_aux     : read_u32(io, endian)?,
+            vd_next    : read_u32(io, endian)?,
+        })
+    }
+}
+
+/// Auxiliary entry of `.gnu.version_d`, naming a version or its parents
+#[derive(Default, Debug, Clone)]
+pub struct ElfVerdaux {
+    pub vda_name : u32,     // offset of the name in the linked string table
+    pub vda_next : u32,     // offset of the next entry, 0 for the last
+}
+
+impl ElfVerdaux {
+    /// Size of an auxiliary version definition in the file
+    pub const SIZE : u64 = 8;
+
+    /// Parse an auxiliary version definition
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<ElfVerdaux, ElfError> 
+    {
+        Ok(ElfVerdaux {
+            vda_name : read_u32(io, endian)?,
+            vda_next : read_u32(io, endian)?,
+        })
+    }
+}
+
+/// Entry of `.gnu.version_r`, one per needed file
+#[derive(Default, Debug, Clone)]
+pub struct ElfVerneed {
+    pub vn_version : u16,   // revision of the structure, 1
+    pub vn_cnt     : u16,   // number of auxiliary entries
+    pub vn_file    : u32,   // offset of the file name in the string table
+    pub vn_aux     : u32,   // offset of the first auxiliary entry
+    pub vn_next    : u32,   // offset of the next entry, 0 for the last
+}
+
+impl ElfVerneed {
+    /// Size of a version requirement in the file
+    pub const SIZE : u64 = 16;
+
+    /// Parse a version requirement
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<ElfVerneed, ElfError> 
+    {
+        Ok(ElfVerneed {
+            vn_version : read_u16(io, endian)?,
+            vn_cnt     : read_u16(io, endian)?,
+            vn_file    : read_u32(io, endian)?,
+            vn_aux     : read_u32(io, endian)?,
+            vn_next    : read_u32(io, endian)?,
+        })
+    }
+}
+
+/// Auxiliary entry of `.gnu.version_r`, one per version needed from a file
+#[derive(Default, Debug, Clone)]
+pub struct ElfVernaux {
+    pub vna_hash  : u32,    // SysV hash of the version name
+    pub vna_flags : u16,    // VER_FLG_*
+    pub vna_other : u16,    // index referenced by `.gnu.version`
+    pub vna_name  : u32,    // offset of the name in the string table
+    pub vna_next  : u32,    // offset of the next entry, 0 for the last
+}
+
+impl ElfVernaux {
+    /// Size of an auxiliary version requirement in the file
+    pub const SIZE : u64 = 16;
+
+    /// Parse an auxiliary version requirement
+    pub fn from_io(io : &mut Cursor<&[u8]>, endian : EiData) 
+        -> Result<ElfVernaux, ElfError> 
+    {
+        Ok(ElfVernaux {
+            vna_hash  : read_u32(io, endian)?,
+            vna_flags : read_u16(io, endian)?,
+            vna_other : read_u16(io, endian)?,
+            vna_name  : read_u32(io, endian)?,
+            vna_next  : read_u32(io, endian)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_enums() {
+        // Listed values, range variants and unknown values all convert back
+        // to the value they came from
+        for &value in &[1u16, 0xfe01, 0xff02, 0x1234] {
+            assert_eq!(u16::from(EType::from(value)), value);
+        }
+        assert_eq!(EType::from(0xfe01), EType::Os(0xfe01));
+        assert_eq!(EType::from(0xff02), EType::Proc(0xff02));
+        assert_eq!(EType::from(0x1234), EType::Unknown(0x1234));
+        assert_eq!(EVersion::from(7), EVersion::Unknown(7));
+        assert_eq!(u32::from(EVersion::Unknown(7)), 7);
+        assert_eq!(EiVersion::from(1), EiVersion::EvCurrent);
+
+        assert_eq!(EType::EtDyn.to_string(), "EtDyn");
+        assert_eq!(EType::Os(0xfe01).to_string(), "Os(0xfe01)");
+        assert_eq!(EType::Proc(0xff02).to_string(), "Proc(0xff02)");
+        assert_eq!(EiVersion::Unknown(2).to_string(), "Unknown(0x2)");
+    }
+}
@@ -0,0 +1,391 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use serde::Serialize;
+
+use crate::elf::{Elf, ElfError, Symbol};
+use crate::elf::helpers::{read_str, read_table, read_u16};
+use crate::elf::types::*;
+
+/// A version defined by the file (`.gnu.version_d`)
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionDef {
+    pub index   : u16,
+    pub flags   : u16,
+    pub name    : String,
+    // Versions this one inherits from
+    pub parents : Vec<String>,
+}
+
+/// Versions required from one of the needed files (`.gnu.version_r`)
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionNeed {
+    pub file     : String,
+    pub versions : Vec<NeededVersion>,
+}
+
+/// A version required from a needed file
+#[derive(Debug, Clone, Serialize)]
+pub struct NeededVersion {
+    pub name  : String,
+    pub index : u16,
+    pub flags : u16,
+}
+
+/// Version a dynamic symbol is bound to (`.gnu.version`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolVersion {
+    pub name   : String,
+    // The version is not the default one, it is only picked by objects
+    // that were linked against it
+    pub hidden : bool,
+}
+
+/// Highest version of a family, such as `GLIBC`, required by the file
+#[derive(Debug, Clone, Serialize)]
+pub struct RequiredVersion {
+    // Full name of the version, as `GLIBC_2.34`
+    pub name    : String,
+    pub family  : String,
+    pub version : Vec<u32>,
+    // Needed file the version is required from
+    pub file    : String,
+    // An imported symbol bound to the version, the reason it is required
+    pub symbol  : Option<String>,
+}
+
+/// Parses a version number such as `2.17`
+pub fn parse_version_number(number : &str) -> Option<Vec<u32>> {
+    number.split('.').map(|n| n.parse().ok()).collect()
+}
+
+/// Compares two version numbers, the missing trailing numbers counting as
+/// zeros so that `2` and `2.0` are equal
+pub fn compare_versions(a : &[u32], b : &[u32]) -> Ordering {
+    let number = |version : &[u32], i| version.get(i).copied().unwrap_or(0);
+    (0..a.len().max(b.len()))
+        .map(|i| number(a, i).cmp(&number(b, i)))
+        .find(|order| order.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Splits a version name such as `GLIBC_2.2.5` into its family and its
+/// number, `None` for names without a number like `GLIBC_PRIVATE`
+pub fn split_version(name : &str) -> Option<(&str, Vec<u32>)> {
+    let (family, number) = name.rsplit_once('_')?;
+    Some((family, parse_version_number(number)?))
+}
+
+impl VersionDef {
+    /// The definition names the file itself rather than a version
+    pub fn is_base(&self) -> bool {
+        self.flags & VER_FLG_BASE != 0
+    }
+}
+
+impl NeededVersion {
+    /// The file still loads when the needed file lacks the version
+    pub fn is_weak(&self) -> bool {
+        self.flags & VER_FLG_WEAK != 0
+    }
+}
+
+impl Elf<'_> {
+    /// Section of type `sh_type` with its string table, `None` when the
+    /// file does not have one
+    fn version_section(&self, sh_type : SHType)
+        -> Result<Option<(Elf64Shdr, &[u8])>, ElfError>
+    {
+        let sections = self.sections();
+        let shdr = match sections.iter().find(|s| s.sh_type == sh_type) {
+            Some(shdr) => shdr.clone(),
+            None => return self.dynamic_version_section(sh_type),
+        };
+        let strtab = sections.get(shdr.sh_link as usize)
+            .ok_or(ElfError::MissingSection {
+                offset : self.shoff(),
+                name   : "linked string table",
+            })?;
+        Ok(Some((shdr, self.section_data(strtab)?)))
+    }
+
+    /// Same table found through `DT_VERDEF` or `DT_VERNEED` and
+    /// `DT_STRTAB`, for files whose section headers were stripped. The
+    /// size of the table is not recorded, it runs up to the end of the file
+    /// and is read as far as the number of entries goes.
+    fn dynamic_version_section(&self, sh_type : SHType)
+        -> Result<Option<(Elf64Shdr, &[u8])>, ElfError>
+    {
+        let (table_tag, count_tag) = match sh_type {
+            SHType::ShtGnuVerdef => (DynTag::DtVerDef, DynTag::DtVerDefNum),
+            SHType::ShtGnuVerneed => (DynTag::DtVerNeed, DynTag::DtVerNeedNum),
+            _ => return Ok(None),
+        };
+        let dynamic = match self.dynamic()? {
+            Some(dynamic) => dynamic,
+            None => return Ok(None),
+        };
+        let offset_of = |tag| dynamic.get(tag)
+            .and_then(|vaddr| self.vaddr_to_offset(vaddr));
+        let (offset, strtab, strsz) = match (offset_of(table_tag),
+                                             offset_of(DynTag::DtStrTab),
+                                             dynamic.get(DynTag::DtStrSz)) {
+            (Some(offset), Some(strtab), Some(strsz)) => (offset, strtab, strsz),
+            _ => return Ok(None),
+        };
+
+        let shdr = Elf64Shdr {
+            sh_type,
+            sh_offset : offset,
+            sh_size   : (self.data().len() as u64).saturating_sub(offset),
+            sh_info   : dynamic.get(count_tag).unwrap_or(0)
+                .min(u32::MAX as u64) as u32,
+            ..Default::default()
+        };
+        Ok(Some((shdr, self.bytes_at(strtab, strsz)?)))
+    }
+
+    /// Version indices of the dynamic symbols (`.gnu.version`), empty when
+    /// the file is not versioned. Without section headers, the table is
+    /// found through `DT_VERSYM` and has one entry per dynamic symbol.
+    pub fn version_indices(&self) -> Result<Vec<u16>, ElfError> {
+        let (offset, size) = match self.sections().iter()
+            .find(|s| s.sh_type == SHType::ShtGnuVersym)
+        {
+            Some(shdr) => (shdr.sh_offset, shdr.sh_size),
+            None => {
+                let offset = self.dynamic()?
+                    .and_then(|dynamic| dynamic.get(DynTag::DtVerSym))
+                    .and_then(|vaddr| self.vaddr_to_offset(vaddr));
+                let tables = match offset {
+                    Some(_) => self.dynamic_lookup_tables()?,
+                    None => None,
+                };
+                match (offset, tables) {
+                    (Some(offset), Some((_, dynsym, _))) => {
+                        let count = dynsym.sh_size / dynsym.sh_entsize.max(1);
+                        (offset, count.saturating_mul(2))
+                    }
+                    _ => return Ok(Vec::new()),
+                }
+            }
+        };
+        let endian = self.endianness();
+        read_table(self.data(), offset, size, 2, |io| read_u16(io, endian))
+    }
+
+    /// Versions defined by the file (`.gnu.version_d`)
+    pub fn version_definitions(&self) -> Result<Vec<VersionDef>, ElfError> {
+        let (shdr, strtab) = match self.version_section(SHType::ShtGnuVerdef)? {
+            Some(section) => section,
+            None => return Ok(Vec::new()),
+        };
+        let endian = self.endianness();
+        let mut io = Cursor::new(self.section_data(&shdr)?);
+        let truncated = |pos| ElfError::Truncated { offset : shdr.sh_offset + pos };
+        let name = |offset : u32| read_str(strtab, offset as u64)
+            .ok_or(ElfError::OutOfRange { offset : offset as u64, size : 1 });
+
+        let mut definitions = Vec::new();
+        let mut pos = 0;
+        for _ in 0..shdr.sh_info {
+            io.set_position(pos);
+            let verdef = ElfVerdef::from_io(&mut io, endian)
+                .map_err(|_| truncated(pos))?;
+
+            let mut names = Vec::new();
+            let mut aux = pos + verdef.vd_aux as u64;
+            for _ in 0..verdef.vd_cnt {
+                io.set_position(aux);
+                let verdaux = ElfVerdaux::from_io(&mut io, endian)
+                    .map_err(|_| truncated(aux))?;
+                names.push(name(verdaux.vda_name)?);
+                if verdaux.vda_next == 0 {
+                    break;
+                }
+                aux += verdaux.vda_next as u64;
+            }
+
+            // The first auxiliary entry names the version, the next ones
+            // its parents
+            let mut names = names.into_iter();
+            definitions.push(VersionDef {
+                index   : verdef.vd_ndx,
+                flags   : verdef.vd_flags,
+                name    : names.next().unwrap_or_default(),
+                parents : names.collect(),
+            });
+            if verdef.vd_next == 0 {
+                break;
+            }
+            pos += verdef.vd_next as u64;
+        }
+        Ok(definitions)
+    }
+
+    /// Versions required from each needed file (`.gnu.version_r`)
+    pub fn version_requirements(&self) -> Result<Vec<VersionNeed>, ElfError> {
+        let (shdr, strtab) = match self.version_section(SHType::ShtGnuVerneed)? {
+            Some(section) => section,
+            None => return Ok(Vec::new()),
+        };
+        let endian = self.endianness();
+        let mut io = Cursor::new(self.section_data(&shdr)?);
+        let truncated = |pos| ElfError::Truncated { offset : shdr.sh_offset + pos };
+        let name = |offset : u32| read_str(strtab, offset as u64)
+            .ok_or(ElfError::OutOfRange { offset : offset as u64, size : 1 });
+
+        let mut requirements = Vec::new();
+        let mut pos = 0;
+        for _ in 0..shdr.sh_info {
+            io.set_position(pos);
+            let verneed = ElfVerneed::from_io(&mut io, endian)
+                .map_err(|_| truncated(pos))?;
+
+            let mut versions = Vec::new();
+            let mut aux = pos + verneed.vn_aux as u64;
+            for _ in 0..verneed.vn_cnt {
+                io.set_position(aux);
+                let vernaux = ElfVernaux::from_io(&mut io, endian)
+                    .map_err(|_| truncated(aux))?;
+                versions.push(NeededVersion {
+                    name  : name(vernaux.vna_name)?,
+                    index : vernaux.vna_other,
+                    flags : vernaux.vna_flags,
+                });
+                if vernaux.vna_next == 0 {
+                    break;
+                }
+                aux += vernaux.vna_next as u64;
+            }
+
+            requirements.push(VersionNeed {
+                file : name(verneed.vn_file)?,
+                versions,
+            });
+            if verneed.vn_next == 0 {
+                break;
+            }
+            pos += verneed.vn_next as u64;
+        }
+        Ok(requirements)
+    }
+
+    /// Names of the versions referenced by `.gnu.version`, by index
+    pub fn version_names(&self) -> Result<HashMap<u16, String>, ElfError> {
+        let mut names = HashMap::new();
+        for def in self.version_definitions()? {
+            if !def.is_base() {
+                names.insert(def.index, def.name);
+            }
+        }
+        for need in self.version_requirements()? {
+            for version in need.versions {
+                names.insert(version.index, version.name);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Annotates the symbols of the dynamic symbol table with their version
+    pub(crate) fn set_versions(&self, symbols : &mut [Symbol])
+        -> Result<(), ElfError>
+    {
+        let indices = self.version_indices()?;
+        if indices.is_empty() {
+            return Ok(());
+        }
+        self.set_versions_at(symbols, &indices)
+    }
+
+    /// Annotates `symbols` with the versions of index `indices`
+    pub(crate) fn set_versions_at(&self, symbols : &mut [Symbol],
+                                  indices : &[u16])
+        -> Result<(), ElfError>
+    {
+        let names = self.version_names()?;
+        for (symbol, &index) in symbols.iter_mut().zip(indices) {
+            let hidden = index & VERSYM_HIDDEN != 0;
+            symbol.version = match index & !VERSYM_HIDDEN {
+                VER_NDX_LOCAL | VER_NDX_GLOBAL => None,
+                index => names.get(&index).map(|name| SymbolVersion {
+                    name : name.clone(),
+                    hidden,
+                }),
+            };
+        }
+        Ok(())
+    }
+
+    /// Highest version of each family required by the file, in the order
+    /// the families first appear in `.gnu.version_r`
+    pub fn max_required_versions(&self)
+        -> Result<Vec<RequiredVersion>, ElfError>
+    {
+        let mut required : Vec<RequiredVersion> = Vec::new();
+        for need in self.version_requirements()? {
+            for needed in need.versions {
+                let (family, version) = match split_version(&needed.name) {
+                    Some(split) => split,
+                    None => continue,
+                };
+                let current = required.iter_mut().find(|r| r.family == family);
+                let higher = |r : &&mut RequiredVersion|
+                    compare_versions(&r.version, &version).is_ge();
+                if current.as_ref().is_some_and(higher) {
+                    continue;
+                }
+                let highest = RequiredVersion {
+                    family  : family.to_string(),
+                    name    : needed.name.clone(),
+                    version,
+                    file    : need.file.clone(),
+                    symbol  : None,
+                };
+                match current {
+                    Some(current) => *current = highest,
+                    None => required.push(highest),
+                }
+            }
+        }
+
+        let imports = self.imports()?;
+        for highest in &mut required {
+            highest.symbol = imports.iter()
+                .find(|sym| sym.version.as_ref()
+                      .is_some_and(|v| v.name == highest.name))
+                .map(|sym| sym.name.clone());
+        }
+        Ok(required)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_versions() {
+        assert_eq!(split_version("GLIBC_2.2.5"), Some(("GLIBC", vec![2, 2, 5])));
+        assert_eq!(split_version("GLIBCXX_3.4.29"),
+                   Some(("GLIBCXX", vec![3, 4, 29])));
+        assert_eq!(split_version("GCC_3.0"), Some(("GCC", vec![3, 0])));
+        // The family is everything before the last underscore
+        assert_eq!(split_version("CXXABI_ARM_1.3.3"),
+                   Some(("CXXABI_ARM", vec![1, 3, 3])));
+        assert_eq!(split_version("GLIBC_PRIVATE"), None);
+        assert_eq!(split_version("GLIBC_2.x"), None);
+        assert_eq!(split_version("libgreet.so.2"), None);
+    }
+
+    #[test]
+    fn compare_numbers() {
+        assert_eq!(compare_versions(&[2], &[2, 0]), Ordering::Equal);
+        assert_eq!(compare_versions(&[2, 0, 0], &[2]), Ordering::Equal);
+        assert_eq!(compare_versions(&[2, 2, 5], &[2, 2]), Ordering::Greater);
+        assert_eq!(compare_versions(&[2, 14], &[2, 2, 5]), Ordering::Greater);
+        assert_eq!(compare_versions(&[2, 4], &[2, 34]), Ordering::Less);
+        assert_eq!(compare_versions(&[], &[0]), Ordering::Equal);
+    }
+}
@@ -0,0 +1,4 @@
+//! Parser for ELF headers, program headers and section headers, along with
+//! detection of the security mitigations a binary was built with
+
+pub mod elf;
@@ -1,46 +1,454 @@
-extern crate num_traits;
-extern crate enum_primitive_derive;
-extern crate colored;
-
 use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
 use std::process;
-use std::fs::File;
-use std::io::Read;
 
-mod elf;
-use elf::*;
+use serde::{Serialize, ser::SerializeStruct};
+
+use elf_parser::elf::*;
+
+mod commands;
+mod policy;
+mod scan;
+
+use policy::Policy;
+use elf_parser::elf::versions::parse_version_number;
+
+/// Output formats of the tool
+#[derive(PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// What to print about the file
+#[derive(PartialEq, Clone, Copy)]
+enum Command {
+    Header,
+    Segments,
+    Sections,
+    Symbols,
+    Dyn,
+    Relocs,
+    Notes,
+    Versions,
+    Checksec,
+}
+
+impl Command {
+    fn from_name(name : &str) -> Option<Command> {
+        Some(match name {
+            "header"   => Command::Header,
+            "segments" => Command::Segments,
+            "sections" => Command::Sections,
+            "symbols"  => Command::Symbols,
+            "dyn"      => Command::Dyn,
+            "relocs"   => Command::Relocs,
+            "notes"    => Command::Notes,
+            "versions" => Command::Versions,
+            "checksec" => Command::Checksec,
+            _ => return None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Header   => "header",
+            Command::Segments => "segments",
+            Command::Sections => "sections",
+            Command::Symbols  => "symbols",
+            Command::Dyn      => "dyn",
+            Command::Relocs   => "relocs",
+            Command::Notes    => "notes",
+            Command::Versions => "versions",
+            Command::Checksec => "checksec",
+        }
+    }
+}
+
+/// Everything reported about a file by `--format json`
+#[derive(Serialize)]
+struct Report {
+    file            : String,
+    header          : Elf64Ehdr,
+    segments        : Vec<Elf64Phdr>,
+    sections        : Vec<Elf64Shdr>,
+    symbols         : Vec<Symbol>,
+    dynamic_symbols : Vec<Symbol>,
+    security        : SecurityOptions,
+}
+
+/// Versions reported by the `versions` command with `--format json`
+#[derive(Serialize)]
+struct Versions {
+    definitions  : Vec<VersionDef>,
+    requirements : Vec<VersionNeed>,
+    highest      : Vec<RequiredVersion>,
+}
+
+/// What a command reports about a file with `--format json`
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Json {
+    Report(Box<Report>),
+    Header(Elf64Ehdr),
+    Segments(Vec<Elf64Phdr>),
+    Sections(Vec<Elf64Shdr>),
+    Symbols(Vec<Symbol>),
+    Dyn(Option<Dynamic>),
+    Relocs(Vec<RelocSection>),
+    Notes(Vec<Note>),
+    Versions(Versions),
+    Checksec(SecurityOptions),
+}
+
+/// What a command reports about one of several files with `--format json`,
+/// serialized as `{ "file": ..., "<command>": ... }`, or with an `error`
+/// instead of the command when the file cannot be parsed
+struct FileJson {
+    file    : String,
+    command : &'static str,
+    json    : Result<Json, String>,
+}
+
+impl Serialize for FileJson {
+    fn serialize<S : serde::Serializer>(&self, serializer : S) 
+        -> Result<S::Ok, S::Error> 
+    {
+        let mut state = serializer.serialize_struct("FileJson", 2)?;
+        state.serialize_field("file", &self.file)?;
+        match &self.json {
+            Ok(json) => state.serialize_field(self.command, json)?,
+            Err(e) => state.serialize_field("error", e)?,
+        }
+        state.end()
+    }
+}
+
+/// Exit code when a file breaks the policy
+const EXIT_POLICY : i32 = 2;
 
 fn usage() {
-    println!("./elf_parser <filename>");
+    println!("./elf_parser [--format text|json] [--no-color] [--demangle] \
+              [--policy <file>] [--max-glibc <version>] [command] <path>...");
+    println!();
+    println!("Commands:");
+    println!("  header     file header");
+    println!("  segments   program headers");
+    println!("  sections   section headers");
+    println!("  symbols    symbol tables");
+    println!("  dyn        dynamic section");
+    println!("  relocs     relocations");
+    println!("  notes      notes");
+    println!("  versions   symbol versions defined and required");
+    println!("  checksec   mitigations (default)");
+    println!();
+    println!("Several paths or a directory, which is scanned recursively, print");
+    println!("the mitigations of every ELF file found as a summary table.");
+    println!("Other commands print each file in turn, with --format json as");
+    println!("one array of {{ \"file\": ..., \"<command>\": ... }} objects.");
+    println!();
+    println!("With --policy, files that do not meet the policy are reported and");
+    println!("the exit code is {}. It is 1 when a file cannot be parsed.", 
+             EXIT_POLICY);
+    println!("--max-glibc 2.17 adds to the policy that files may not require");
+    println!("a GLIBC version above 2.17.");
+    println!();
+    println!("--demangle prints C++ and Rust symbol names demangled, it needs");
+    println!("the `demangle` feature.");
 }
 
 fn main() {
-    let args : Vec<String> = env::args().collect();
-    if args.len() < 2 {
+    let mut format = Format::Text;
+    let mut color = io::stdout().is_terminal();
+    let mut demangle = false;
+    let mut command = None;
+    let mut policy = None;
+    let mut max_glibc = None;
+    let mut paths = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().as_deref() {
+                Some("text") => format = Format::Text,
+                Some("json") => format = Format::Json,
+                _ => {
+                    usage();
+                    process::exit(1);
+                }
+            },
+            "--no-color" => color = false,
+            "--demangle" if cfg!(feature = "demangle") => demangle = true,
+            "--demangle" => {
+                eprintln!("--demangle: built without the `demangle` feature");
+                process::exit(1);
+            }
+            "--policy" => match args.next().map(Policy::load) {
+                Some(Ok(p)) => policy = Some(p),
+                Some(Err(e)) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+                None => {
+                    usage();
+                    process::exit(1);
+                }
+            },
+            "--max-glibc" => match args.next().as_deref()
+                .and_then(parse_version_number)
+            {
+                Some(version) => max_glibc = Some(version),
+                None => {
+                    usage();
+                    process::exit(1);
+                }
+            },
+            _ if command.is_none() && paths.is_empty()
+                && Command::from_name(&arg).is_some() =>
+                command = Command::from_name(&arg),
+            _ if !arg.starts_with("--") => paths.push(arg),
+            _ => {
+                usage();
+                process::exit(1);
+            }
+        }
+    }
+    if paths.is_empty() {
         usage();
         process::exit(1);
     }
-    let filename = &args[1];
-    
-    let mut file = File::open(filename).expect("File not found");
-    let mut buf = [0; 5];
-    file.read_exact(&mut buf);
-    if buf[4] == 2u8 {
-        let mut elf_file = ELF64::load(filename).unwrap();
-        elf_file.mitigations = SecurityOptions::get_options_64(&elf_file, &mut file)
-            .unwrap();
-        println!("Entry point\t: 0x{:08x}", elf_file.header.e_entry);
-        println!("Machine\t\t: {}", elf_file.header.e_machine);
-        println!("Class\t\t: {}", elf_file.header.e_ident.class);
-        println!("{}", elf_file.mitigations);
-    } else {
-        let mut elf_file = ELF32::load(filename).unwrap();
-        elf_file.mitigations = SecurityOptions::get_options_32(&elf_file, &mut file)
-            .unwrap();
-        println!("Entry point\t: 0x{:08x}", elf_file.header.e_entry);
-        println!("Machine\t\t: {}", elf_file.header.e_machine);
-        println!("Class\t\t: {}", elf_file.header.e_ident.class);
-        println!("{}", elf_file.mitigations);
-    }
-    
+
+    if !color {
+        colored::control::set_override(false);
+    }
+    if let Some(version) = max_glibc {
+        policy.get_or_insert_with(Policy::default).set_max_glibc(version);
+    }
+
+    // Once the reader of the output closes it, as `head` does, nothing
+    // more is printed but the files are still checked against the policy
+    let mut out = io::stdout().lock();
+
+    // A single file is printed in full, anything else is scanned
+    if paths.len() == 1 && !Path::new(&paths[0]).is_dir() {
+        match run(&mut out, &paths[0], command, &format, demangle) {
+            Ok(()) => {}
+            Err(e) if e.is_broken_pipe() => {}
+            Err(e) => {
+                eprintln!("{}: {}", paths[0], e);
+                process::exit(1);
+            }
+        }
+        if let Some(policy) = &policy {
+            match check_policy(&paths[0], policy) {
+                Ok(true) => {}
+                Ok(false) => process::exit(EXIT_POLICY),
+                Err(e) => {
+                    eprintln!("{}: {}", paths[0], e);
+                    process::exit(1);
+                }
+            }
+        }
+        return;
+    }
+
+    let files = scan::collect(&paths);
+    let mut failed = false;
+    let mut violated = false;
+    let mut closed = false;
+    match command {
+        // The outputs are gathered in a single array, without the headers
+        // naming each file
+        Some(command) if command != Command::Checksec 
+            && format == Format::Json => 
+        {
+            let mut outputs = Vec::new();
+            for file in &files {
+                let file = file.display().to_string();
+                let json = Elf::load(&file).map_err(commands::Error::from)
+                    .and_then(|elf_file| json(&elf_file, &file, Some(command),
+                                              demangle))
+                    .map_err(|e| {
+                        eprintln!("{}: {}", file, e);
+                        failed = true;
+                        e.to_string()
+                    });
+                if let Some(policy) = &policy {
+                    match check_policy(&file, policy) {
+                        Ok(complies) => violated |= !complies,
+                        Err(e) => {
+                            eprintln!("{}: {}", file, e);
+                            failed = true;
+                        }
+                    }
+                }
+                outputs.push(FileJson { file, command : command.name(), json });
+            }
+            match writeln!(out, "{}", 
+                           serde_json::to_string_pretty(&outputs).unwrap()) {
+                Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                    eprintln!("{}", e);
+                    failed = true;
+                }
+                _ => {}
+            }
+        }
+        Some(command) if command != Command::Checksec => {
+            for file in &files {
+                let file = file.display().to_string();
+                let printed = match closed {
+                    true => Ok(()),
+                    false => writeln!(out)
+                        .and_then(|()| writeln!(out, "File: {}", file))
+                        .map_err(commands::Error::from)
+                        .and_then(|()| run(&mut out, &file, Some(command),
+                                           &format, demangle)),
+                };
+                match printed {
+                    Ok(()) => {}
+                    Err(e) if e.is_broken_pipe() => closed = true,
+                    Err(e) => {
+                        eprintln!("{}: {}", file, e);
+                        failed = true;
+                    }
+                }
+                if let Some(policy) = &policy {
+                    match check_policy(&file, policy) {
+                        Ok(complies) => violated |= !complies,
+                        Err(e) => {
+                            eprintln!("{}: {}", file, e);
+                            failed = true;
+                        }
+                    }
+                }
+            }
+        }
+        _ => {
+            let results = scan::scan(&files);
+            failed = results.iter().any(|result| result.error.is_some());
+            let printed = match format {
+                Format::Json => writeln!(out, "{}", 
+                    serde_json::to_string_pretty(&results).unwrap()),
+                Format::Text => scan::print_summary(&mut out, &results),
+            };
+            match printed {
+                Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                    eprintln!("{}", e);
+                    failed = true;
+                }
+                _ => {}
+            }
+
+            if let Some(policy) = &policy {
+                for result in &results {
+                    if let (Some(machine), Some(secop)) = 
+                        (&result.machine, &result.security)
+                    {
+                        let violations = policy.violations(
+                            machine, secop, result.glibc.as_ref());
+                        report_violations(&result.file, &violations);
+                        violated |= !violations.is_empty();
+                    }
+                }
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+    if violated {
+        process::exit(EXIT_POLICY);
+    }
+}
+
+/// Prints the ways a file breaks the policy
+fn report_violations(filename : &str, violations : &[String]) {
+    for violation in violations {
+        eprintln!("{}: policy violation: {}", filename, violation);
+    }
+}
+
+/// Checks the file located at `filename` against `policy`, returning
+/// whether it complies
+fn check_policy(filename : &str, policy : &Policy) -> Result<bool, ElfError> {
+    let elf_file = Elf::load(filename)?;
+    let secop = SecurityOptions::get_options(&elf_file)?;
+    let glibc = commands::glibc_version(&elf_file)?;
+    let violations = policy.violations(elf_file.machine(), &secop, 
+                                       glibc.as_ref());
+    report_violations(filename, &violations);
+    Ok(violations.is_empty())
+}
+
+/// What `command` reports about `elf_file`, located at `filename`, with
+/// `--format json`
+fn json(elf_file : &Elf, filename : &str, command : Option<Command>,
+        demangle : bool) 
+    -> Result<Json, commands::Error> 
+{
+    Ok(match command {
+        None => Json::Report(Box::new(Report {
+            file            : filename.to_string(),
+            header          : elf_file.header(),
+            segments        : elf_file.segments().to_vec(),
+            sections        : elf_file.sections().to_vec(),
+            symbols         : commands::demangle_symbols(
+                elf_file.symbols()?, demangle),
+            dynamic_symbols : commands::demangle_symbols(
+                elf_file.dynamic_symbols()?, demangle),
+            security        : SecurityOptions::get_options(elf_file)?,
+        })),
+        Some(Command::Header) => Json::Header(elf_file.header()),
+        Some(Command::Segments) => Json::Segments(elf_file.segments().to_vec()),
+        Some(Command::Sections) => Json::Sections(elf_file.sections().to_vec()),
+        Some(Command::Symbols) => {
+            let mut symbols = elf_file.symbols()?;
+            symbols.extend(elf_file.dynamic_symbols()?);
+            Json::Symbols(commands::demangle_symbols(symbols, demangle))
+        }
+        Some(Command::Dyn) => Json::Dyn(elf_file.dynamic()?),
+        Some(Command::Relocs) => {
+            let mut sections = elf_file.relocations()?;
+            for reloc in sections.iter_mut().flat_map(|s| &mut s.relocs) {
+                reloc.symbol = reloc.symbol.as_deref()
+                    .map(|name| commands::symbol_name(name, demangle));
+            }
+            Json::Relocs(sections)
+        }
+        Some(Command::Notes) => Json::Notes(elf_file.notes()?),
+        Some(Command::Versions) => Json::Versions(Versions {
+            definitions  : elf_file.version_definitions()?,
+            requirements : elf_file.version_requirements()?,
+            highest      : commands::runtime_versions(elf_file, demangle)?,
+        }),
+        Some(Command::Checksec) => 
+            Json::Checksec(SecurityOptions::get_options(elf_file)?),
+    })
+}
+
+fn run(out : &mut dyn Write, filename : &str, command : Option<Command>,
+       format : &Format, demangle : bool) 
+    -> Result<(), commands::Error> 
+{
+    let elf_file = Elf::load(filename)?;
+
+    if *format == Format::Json {
+        // Serializing plain structs into a String does not fail
+        let json = json(&elf_file, filename, command, demangle)?;
+        writeln!(out, "{}", serde_json::to_string_pretty(&json).unwrap())?;
+        return Ok(());
+    }
+
+    match command.unwrap_or(Command::Checksec) {
+        Command::Header   => commands::header(out, &elf_file),
+        Command::Segments => commands::segments(out, &elf_file),
+        Command::Sections => commands::sections(out, &elf_file),
+        Command::Symbols  => commands::symbols(out, &elf_file, demangle),
+        Command::Dyn      => commands::dynamic(out, &elf_file),
+        Command::Relocs   => commands::relocs(out, &elf_file, demangle),
+        Command::Notes    => commands::notes(out, &elf_file),
+        Command::Versions => commands::versions(out, &elf_file, demangle),
+        Command::Checksec => commands::checksec(out, &elf_file),
+    }
 }
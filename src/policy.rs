@@ -0,0 +1,293 @@
+//! Hardening policy that files are checked against, for use as a build gate
+//!
+//! A policy is a text file of `key = value` lines, `#` starts a comment:
+//!
+//! ```text
+//! relro   = full      # full, partial or none
+//! pie     = true
+//! nx      = true
+//! canary  = true
+//! cet     = true      # IBT and SHSTK, on x86 only
+//! fortify = true
+//! rwx     = false     # no writable and executable segment
+//! textrel = false
+//! max_glibc = 2.17    # highest GLIBC_x.y version the file may require
+//! ```
+//!
+//! `ibt`, `shstk`, `bti` and `pac` can also be required one by one, BTI and
+//! PAC are only checked on AArch64.
+
+use std::fs;
+use std::path::Path;
+
+use elf_parser::elf::*;
+use elf_parser::elf::versions::{compare_versions, parse_version_number};
+
+/// Mitigations required by the policy, `None` when the policy does not care
+#[derive(Debug, Default)]
+pub struct Policy {
+    canary    : Option<bool>,
+    nx        : Option<bool>,
+    relro     : Option<RelRo>,
+    pie       : Option<bool>,
+    ibt       : Option<bool>,
+    shstk     : Option<bool>,
+    bti       : Option<bool>,
+    pac       : Option<bool>,
+    fortify   : Option<bool>,
+    rwx       : Option<bool>,
+    textrel   : Option<bool>,
+    max_glibc : Option<Vec<u32>>,
+}
+
+/// Parses the value of a boolean key
+fn parse_bool(value : &str) -> Option<bool> {
+    match value {
+        "true" | "yes" => Some(true),
+        "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Orders RELRO levels from the weakest to the strongest
+fn relro_level(relro : RelRo) -> u8 {
+    match relro {
+        RelRo::NoRelRo => 0,
+        RelRo::PartialRelRo => 1,
+        RelRo::FullRelRo => 2,
+    }
+}
+
+impl Policy {
+    /// Reads the policy file located at `path`
+    pub fn load<P : AsRef<Path>>(path : P) -> Result<Policy, String> {
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+        Policy::parse(&text)
+            .map_err(|e| format!("{}: {}", path.as_ref().display(), e))
+    }
+
+    /// Parses the content of a policy file
+    pub fn parse(text : &str) -> Result<Policy, String> {
+        let mut policy = Policy::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once('=')
+                .ok_or(format!("line {}: expected `key = value`", number + 1))?;
+            let (key, value) = (key.trim(), value.trim().to_lowercase());
+
+            let invalid = || format!("line {}: invalid value `{}` for `{}`",
+                                     number + 1, value, key);
+            let boolean = || parse_bool(&value).ok_or_else(&invalid);
+            match key {
+                "canary"  => policy.canary = Some(boolean()?),
+                "nx"      => policy.nx = Some(boolean()?),
+                "pie"     => policy.pie = Some(boolean()?),
+                "ibt"     => policy.ibt = Some(boolean()?),
+                "shstk"   => policy.shstk = Some(boolean()?),
+                "bti"     => policy.bti = Some(boolean()?),
+                "pac"     => policy.pac = Some(boolean()?),
+                "fortify" => policy.fortify = Some(boolean()?),
+                "rwx"     => policy.rwx = Some(boolean()?),
+                "textrel" => policy.textrel = Some(boolean()?),
+                "cet" => {
+                    policy.ibt = Some(boolean()?);
+                    policy.shstk = Some(boolean()?);
+                }
+                "max_glibc" => policy.max_glibc = Some(
+                    parse_version_number(&value).ok_or_else(&invalid)?),
+                "relro" => policy.relro = Some(match value.as_str() {
+                    "full" => RelRo::FullRelRo,
+                    "partial" => RelRo::PartialRelRo,
+                    "none" => RelRo::NoRelRo,
+                    _ => return Err(invalid()),
+                }),
+                _ => return Err(format!("line {}: unknown key `{}`",
+                                        number + 1, key)),
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Sets the highest `GLIBC` version files may require
+    pub fn set_max_glibc(&mut self, version : Vec<u32>) {
+        self.max_glibc = Some(version);
+    }
+
+    /// Describes how a file built for `machine` with the mitigations `secop`,
+    /// requiring at most the `glibc` version, breaks the policy, empty when
+    /// it complies
+    pub fn violations(&self, machine : &EMachine, secop : &SecurityOptions,
+                      glibc : Option<&RequiredVersion>)
+        -> Vec<String>
+    {
+        let x86 = matches!(machine, EMachine::Em386 | EMachine::Emx86_64);
+        let aarch64 = *machine == EMachine::EmAArch64;
+        let mut violations = Vec::new();
+
+        // A mitigation the policy requires but the file lacks
+        let mut require = |wanted : Option<bool>, applies : bool,
+                           present : bool, name : &str| {
+            if wanted == Some(true) && applies && !present {
+                violations.push(format!("{} is required", name));
+            }
+        };
+        require(self.canary, true, secop.canary(), "stack canary");
+        require(self.nx, true, secop.nx(), "NX");
+        require(self.pie, !matches!(secop.pie(), Pie::Relocatable | Pie::Core),
+                secop.pie().is_pie(), "PIE");
+        require(self.ibt, x86, secop.ibt(), "IBT");
+        require(self.shstk, x86, secop.shstk(), "SHSTK");
+        require(self.bti, aarch64, secop.bti(), "BTI");
+        require(self.pac, aarch64, secop.pac(), "PAC");
+        require(self.fortify, secop.fortifiable() > 0, secop.fortify(),
+                "FORTIFY");
+
+        if let Some(relro) = self.relro {
+            if relro_level(secop.relro()) < relro_level(relro) {
+                violations.push(format!("RELRO is {}, {} is required",
+                                        secop.relro(), relro));
+            }
+        }
+        if self.rwx == Some(false) && secop.rwx() {
+            violations.push("writable and executable segment".to_string());
+        }
+        if self.textrel == Some(false) && secop.textrel() {
+            violations.push("relocations in the text segment".to_string());
+        }
+        if let (Some(max), Some(glibc)) = (&self.max_glibc, glibc) {
+            if compare_versions(&glibc.version, max).is_gt() {
+                let max : Vec<String> = max.iter().map(u32::to_string).collect();
+                let reason = match &glibc.symbol {
+                    Some(symbol) => format!(" by {}", symbol),
+                    None => String::new(),
+                };
+                violations.push(format!("{} is required{}, at most GLIBC_{} \
+                                         is allowed", glibc.name, reason,
+                                        max.join(".")));
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Highest `GLIBC` version required by `symbol`
+    fn glibc(version : &[u32], symbol : Option<&str>) -> RequiredVersion {
+        let number : Vec<String> = version.iter().map(u32::to_string).collect();
+        RequiredVersion {
+            name    : format!("GLIBC_{}", number.join(".")),
+            family  : "GLIBC".to_string(),
+            version : version.to_vec(),
+            file    : "libc.so.6".to_string(),
+            symbol  : symbol.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn parse_keys() {
+        let policy = Policy::parse("\
+            # Hardening required of the release builds\n\
+            relro   = full\n\
+            pie     = true      # position independent\n\
+            nx      = yes\n\
+            canary  = True\n\
+            cet     = true\n\
+            bti     = no\n\
+            fortify = true\n\
+            rwx     = false\n\
+            textrel = false\n\
+            \n\
+            max_glibc = 2.17\n").unwrap();
+
+        assert_eq!(policy.relro, Some(RelRo::FullRelRo));
+        assert_eq!(policy.pie, Some(true));
+        assert_eq!(policy.nx, Some(true));
+        assert_eq!(policy.canary, Some(true));
+        assert_eq!((policy.ibt, policy.shstk), (Some(true), Some(true)));
+        assert_eq!((policy.bti, policy.pac), (Some(false), None));
+        assert_eq!(policy.fortify, Some(true));
+        assert_eq!((policy.rwx, policy.textrel), (Some(false), Some(false)));
+        assert_eq!(policy.max_glibc, Some(vec![2, 17]));
+
+        let empty = Policy::parse("# nothing required\n\n").unwrap();
+        assert!(empty.canary.is_none() && empty.relro.is_none());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Policy::parse("pie = maybe").unwrap_err(),
+                   "line 1: invalid value `maybe` for `pie`");
+        assert_eq!(Policy::parse("nx = true\nrelro = half").unwrap_err(),
+                   "line 2: invalid value `half` for `relro`");
+        assert_eq!(Policy::parse("max_glibc = 2.x").unwrap_err(),
+                   "line 1: invalid value `2.x` for `max_glibc`");
+        assert_eq!(Policy::parse("aslr = true").unwrap_err(),
+                   "line 1: unknown key `aslr`");
+        assert_eq!(Policy::parse("\npie").unwrap_err(),
+                   "line 2: expected `key = value`");
+    }
+
+    #[test]
+    fn violations() {
+        // Without canary, NX, PIE nor RELRO
+        let secop = SecurityOptions::default();
+        let x86_64 = EMachine::Emx86_64;
+
+        let policy = Policy::parse("pie = true\nrelro = full\nnx = true\n\
+                                    canary = true\nrwx = false").unwrap();
+        assert_eq!(policy.violations(&x86_64, &secop, None), [
+            "stack canary is required",
+            "NX is required",
+            "PIE is required",
+            "RELRO is NoRelRo, FullRelRo is required",
+        ]);
+
+        // BTI only applies to AArch64, IBT to x86, FORTIFY to files calling
+        // fortifiable functions
+        let policy = Policy::parse("bti = true\nrelro = none\nfortify = true")
+            .unwrap();
+        assert!(policy.violations(&x86_64, &secop, None).is_empty());
+        assert_eq!(policy.violations(&EMachine::EmAArch64, &secop, None),
+                   ["BTI is required"]);
+        let policy = Policy::parse("ibt = true").unwrap();
+        assert_eq!(policy.violations(&x86_64, &secop, None), ["IBT is required"]);
+        assert!(policy.violations(&EMachine::EmAArch64, &secop, None)
+                .is_empty());
+        assert!(Policy::default().violations(&x86_64, &secop, None).is_empty());
+    }
+
+    #[test]
+    fn max_glibc() {
+        let secop = SecurityOptions::default();
+        let x86_64 = EMachine::Emx86_64;
+        let glibc_2_34 = glibc(&[2, 34], Some("__libc_start_main"));
+        let violations = |max : &str, glibc : &RequiredVersion| {
+            Policy::parse(&format!("max_glibc = {}", max)).unwrap()
+                .violations(&x86_64, &secop, Some(glibc))
+        };
+
+        assert!(violations("2.34", &glibc_2_34).is_empty());
+        assert!(violations("2.34.0", &glibc_2_34).is_empty());
+        assert!(violations("3", &glibc_2_34).is_empty());
+        assert_eq!(violations("2.17", &glibc_2_34),
+                   ["GLIBC_2.34 is required by __libc_start_main, at most \
+                     GLIBC_2.17 is allowed"]);
+        assert_eq!(violations("2.4", &glibc_2_34).len(), 1);
+
+        // Missing numbers count as zeros
+        let glibc_2_0 = glibc(&[2, 0], None);
+        assert!(violations("2", &glibc_2_0).is_empty());
+        assert_eq!(violations("1.9", &glibc_2_0),
+                   ["GLIBC_2.0 is required, at most GLIBC_1.9 is allowed"]);
+        assert!(Policy::default().violations(&x86_64, &secop, Some(&glibc_2_0))
+                .is_empty());
+    }
+}
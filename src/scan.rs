@@ -0,0 +1,224 @@
+//! Analysis of many files at once, such as a whole root filesystem
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use colored::*;
+use serde::Serialize;
+
+use elf_parser::elf::*;
+
+use crate::commands;
+
+/// Outcome of the analysis of one file
+#[derive(Serialize)]
+pub struct ScanResult {
+    pub file     : String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub machine  : Option<EMachine>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security : Option<SecurityOptions>,
+    // Highest `GLIBC` version the file requires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glibc    : Option<RequiredVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error    : Option<String>,
+}
+
+/// The file starts with the `ELF` magic
+fn is_elf(path : &Path) -> bool {
+    let mut magic = [0; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok() && magic == [0x7f, 0x45, 0x4c, 0x46]
+}
+
+/// Appends the `ELF` files found under `dir` to `files`. Symbolic links are
+/// not followed, they would only report the same file twice or loop.
+fn walk(dir : &Path, files : &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}: {}", dir.display(), e);
+            return;
+        }
+    };
+
+    let mut paths : Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    paths.sort();
+    for path in paths {
+        match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.is_dir() => walk(&path, files),
+            Ok(meta) if meta.is_file() && is_elf(&path) => files.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// Files named by `paths` and the `ELF` files found in the directories among
+/// them. Files found in directories that do not start with the `ELF` magic
+/// are skipped, files named explicitly are kept so that they are reported
+/// as errors.
+pub fn collect(paths : &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths.iter().map(Path::new) {
+        match path.is_dir() {
+            true => walk(path, &mut files),
+            false => files.push(path.to_path_buf()),
+        }
+    }
+    files
+}
+
+/// Analyzes one file
+fn analyze(path : &Path) -> ScanResult {
+    let file = path.display().to_string();
+    let result = Elf::load(path).and_then(|elf| {
+        Ok((*elf.machine(), SecurityOptions::get_options(&elf)?,
+            commands::glibc_version(&elf)?))
+    });
+
+    match result {
+        Ok((machine, security, glibc)) => ScanResult {
+            file,
+            machine  : Some(machine),
+            security : Some(security),
+            glibc,
+            error    : None,
+        },
+        Err(e) => ScanResult {
+            file,
+            machine  : None,
+            security : None,
+            glibc    : None,
+            error    : Some(e.to_string()),
+        },
+    }
+}
+
+/// Analyzes `files` on as many threads as there are cores, returning the
+/// results in the order of `files`
+pub fn scan(files : &[PathBuf]) -> Vec<ScanResult> {
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(files.len().max(1));
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(files.len()));
+
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let path = match files.get(index) {
+                    Some(path) => path,
+                    None => break,
+                };
+                let result = analyze(path);
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Colors a mitigation depending on whether it is enabled
+fn yes_no(enabled : bool) -> ColoredString {
+    match enabled {
+        true => "yes".green(),
+        false => "no".red(),
+    }
+}
+
+/// Prints one row per file and the number of files lacking each mitigation
+pub fn print_summary(out : &mut dyn Write, results : &[ScanResult])
+    -> io::Result<()>
+{
+    writeln!(out, "{:<7} {:<7} {:<13} {:<14} {:<5} {:<5} {:<5} {:<5} {:<12} \
+              File",
+             "Canary", "NX", "RELRO", "PIE", "IBT", "SHSTK", "BTI", "PAC",
+             "FORTIFY")?;
+
+    for result in results {
+        let secop = match &result.security {
+            Some(secop) => secop,
+            None => {
+                writeln!(out, "{:<80} {}",
+                         result.error.as_deref().unwrap_or_default().red(),
+                         result.file)?;
+                continue;
+            }
+        };
+        let relro = match secop.relro() {
+            RelRo::FullRelRo => secop.relro().to_string().green(),
+            RelRo::PartialRelRo => secop.relro().to_string().yellow(),
+            RelRo::NoRelRo => secop.relro().to_string().red(),
+        };
+        let pie = match secop.pie() {
+            Pie::NoPie => secop.pie().to_string().red(),
+            pie => pie.to_string().green(),
+        };
+        let fortify = format!("{} ({}/{})", if secop.fortify() { "yes" } else { "no" },
+                              secop.fortified(), secop.fortifiable());
+        let fortify = match secop.fortify() || secop.fortifiable() == 0 {
+            true => fortify.green(),
+            false => fortify.red(),
+        };
+        writeln!(out, "{:<7} {:<7} {:<13} {:<14} {:<5} {:<5} {:<5} {:<5} \
+                  {:<12} {}",
+                 yes_no(secop.canary()), yes_no(secop.nx()), relro, pie,
+                 yes_no(secop.ibt()), yes_no(secop.shstk()),
+                 yes_no(secop.bti()), yes_no(secop.pac()), fortify,
+                 result.file)?;
+    }
+
+    // Control-flow protection is only counted on the architectures that
+    // provide it
+    let analyzed : Vec<(&EMachine, &SecurityOptions)> = results.iter()
+        .filter_map(|r| Some((r.machine.as_ref()?, r.security.as_ref()?)))
+        .collect();
+    let missing = |check : &dyn Fn(&EMachine, &SecurityOptions) -> Option<bool>|
+        -> (usize, usize)
+    {
+        let applicable : Vec<bool> = analyzed.iter()
+            .filter_map(|(machine, secop)| check(machine, secop))
+            .collect();
+        (applicable.iter().filter(|&&ok| !ok).count(), applicable.len())
+    };
+    let x86 = |m : &EMachine| matches!(m, EMachine::Em386 | EMachine::Emx86_64);
+    let aarch64 = |m : &EMachine| *m == EMachine::EmAArch64;
+
+    let counts = [
+        ("Canary", missing(&|_, s| Some(s.canary()))),
+        ("NX", missing(&|_, s| Some(s.nx()))),
+        ("Full RELRO", missing(&|_, s| Some(s.relro() == RelRo::FullRelRo))),
+        ("PIE", missing(&|_, s| match s.pie() {
+            Pie::Relocatable | Pie::Core => None,
+            pie => Some(pie.is_pie()),
+        })),
+        ("IBT", missing(&|m, s| x86(m).then(|| s.ibt()))),
+        ("SHSTK", missing(&|m, s| x86(m).then(|| s.shstk()))),
+        ("BTI", missing(&|m, s| aarch64(m).then(|| s.bti()))),
+        ("PAC", missing(&|m, s| aarch64(m).then(|| s.pac()))),
+        ("FORTIFY", missing(&|_, s| (s.fortifiable() > 0).then(|| s.fortify()))),
+    ];
+
+    let errors = results.len() - analyzed.len();
+    writeln!(out)?;
+    writeln!(out, "{} files analyzed, {} could not be parsed",
+             analyzed.len(), errors)?;
+    writeln!(out, "Files lacking each mitigation:")?;
+    for (name, (missing, total)) in counts {
+        writeln!(out, "  {:<12}: {}/{}", name, missing, total)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Parses the files of `tests/corpus`, generated by `tests/corpus/generate.py`,
+//! and checks that big-endian files decode to the same values as their 
+//! little-endian counterpart
+
+use elf_parser::elf::*;
+
+mod common;
+
+#[test]
+fn mips32_msb() {
+    let elf = ELF32::load(common::path("mips32-msb.elf")).unwrap();
+
+    assert_eq!(elf.header.e_ident.endianness, EiData::ElfData2Msb);
+    assert_eq!(elf.header.e_type, EType::EtExec);
+    assert_eq!(elf.header.e_machine, EMachine::EmMips);
+    assert_eq!(elf.header.e_entry, 0x400100);
+    assert_eq!(elf.header.e_phoff, 52);
+    assert_eq!(elf.header.e_shstrndx, 4);
+
+    assert_eq!(elf.pht.len(), 3);
+    assert_eq!(elf.pht[0].p_type, PType::PtLoad);
+    assert_eq!(elf.pht[0].p_vaddr, 0x400000);
+    assert_eq!(elf.pht[0].p_align, 0x1000);
+    assert!(elf.pht[0].has_r() && elf.pht[0].has_x() && !elf.pht[0].has_w());
+    assert_eq!(elf.pht[1].p_type, PType::PtGnuStack);
+    assert_eq!(elf.pht[2].p_type, PType::PtGnuRelro);
+
+    assert_eq!(elf.sht.len(), 5);
+    assert_eq!(elf.sht[1].sh_type, SHType::ShtPROGBITS);
+    assert_eq!(elf.sht[1].sh_addr, 0x400094);
+    assert_eq!(elf.sht[2].sh_type, SHType::ShtSYMTAB);
+    assert_eq!(elf.sht[2].name, ".symtab");
+    assert_eq!(elf.sht[2].sh_link, 3);
+    assert_eq!(elf.sht[2].sh_entsize, 16);
+}
+
+#[test]
+fn ppc64_msb() {
+    let elf = ELF64::load(common::path("ppc64-msb.elf")).unwrap();
+
+    assert_eq!(elf.header.e_ident.endianness, EiData::ElfData2Msb);
+    assert_eq!(elf.header.e_type, EType::EtExec);
+    assert_eq!(elf.header.e_machine, EMachine::EmPPC64);
+    assert_eq!(elf.header.e_entry, 0x10000400);
+    assert_eq!(elf.header.e_phoff, 64);
+
+    assert_eq!(elf.pht.len(), 3);
+    assert_eq!(elf.pht[0].p_type, PType::PtLoad);
+    assert_eq!(elf.pht[0].p_vaddr, 0x400000);
+
+    assert_eq!(elf.sht.len(), 5);
+    assert_eq!(elf.sht[2].sh_type, SHType::ShtSYMTAB);
+    assert_eq!(elf.sht[2].sh_entsize, 24);
+}
+
+#[test]
+fn msb_matches_lsb() {
+    let msb = ELF64::load(common::path("ppc64-msb.elf")).unwrap();
+    let lsb = ELF64::load(common::path("x86_64-lsb.elf")).unwrap();
+
+    assert_eq!(lsb.header.e_ident.endianness, EiData::ElfData2Lsb);
+    assert_eq!(msb.header.e_shoff, lsb.header.e_shoff);
+    for (m, l) in msb.pht.iter().zip(lsb.pht.iter()) {
+        assert_eq!(m.p_type, l.p_type);
+        assert_eq!(m.p_offset, l.p_offset);
+        assert_eq!(m.p_flags, l.p_flags);
+    }
+    for (m, l) in msb.sht.iter().zip(lsb.sht.iter()) {
+        assert_eq!(m.sh_name, l.sh_name);
+        assert_eq!(m.sh_type, l.sh_type);
+        assert_eq!(m.sh_offset, l.sh_offset);
+        assert_eq!(m.sh_size, l.sh_size);
+    }
+}
+
+#[test]
+fn msb_security_options() {
+    let path = common::path("mips32-msb.elf");
+    let elf = Elf::load(&path).unwrap();
+
+    assert_eq!(elf.machine(), &EMachine::EmMips);
+    assert_eq!(elf.entry(), 0x400100);
+    assert!(SecurityOptions::get_options(&elf).is_ok());
+}
+
+#[test]
+fn msb_symbols() {
+    for file in &["mips32-msb.elf", "ppc64-msb.elf"] {
+        let elf = common::load(file);
+        let symbols = elf.symbols().unwrap();
+
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[1].name, "main");
+        assert_eq!(symbols[1].size, 16);
+        assert_eq!(symbols[1].bind, SymBind::StbGlobal);
+        assert_eq!(symbols[1].sym_type, SymType::SttFunc);
+        assert_eq!(symbols[1].shndx, 1);
+        assert_eq!(symbols[2].name, "__stack_chk_fail");
+        assert!(symbols[2].is_import());
+    }
+}
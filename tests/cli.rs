@@ -0,0 +1,222 @@
+//! Runs the `elf_parser` binary on the files of `tests/corpus` and checks its
+//! output and exit codes: 2 when a file breaks the policy, 1 when a file or the
+//! policy cannot be parsed
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+mod common;
+
+/// Writes `text` to a policy file private to the test named `name`
+fn policy(name : &str, text : &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("elf_parser-{}-{}.policy", name, std::process::id()));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+/// Runs `elf_parser` with `args`
+fn elf_parser(args : &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_elf_parser"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Exit code of `elf_parser` run with `args`
+fn exit_code(args : &[&str]) -> i32 {
+    elf_parser(args).status.code().unwrap()
+}
+
+#[test]
+fn policy_exit_codes() {
+    let path = policy("pie", "pie = true\nnx = true\n");
+    let pie = path.to_str().unwrap();
+    let exec = common::path("x86_64-exec.elf");
+    let pie_exec = common::path("x86_64-pie.elf");
+    let not_elf = common::path("generate.py");
+
+    assert_eq!(exit_code(&["--policy", pie, &pie_exec]), 0);
+    assert_eq!(exit_code(&["--policy", pie, &exec]), 2);
+    assert_eq!(exit_code(&["--policy", pie, "checksec", &exec]), 2);
+    // Scanned files are all checked, one violation is enough
+    assert_eq!(exit_code(&["--policy", pie, &pie_exec, &exec]), 2);
+    // A file that cannot be parsed is not a policy violation
+    assert_eq!(exit_code(&["--policy", pie, &not_elf]), 1);
+    assert_eq!(exit_code(&[&not_elf]), 1);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn invalid_policy() {
+    let invalid = policy("invalid", "pie = maybe\n");
+    let exec = common::path("x86_64-exec.elf");
+
+    assert_eq!(exit_code(&["--policy", invalid.to_str().unwrap(), &exec]), 1);
+    assert_eq!(exit_code(&["--policy", "/nonexistent.policy", &exec]), 1);
+    assert_eq!(exit_code(&["--max-glibc", "2.x", &exec]), 1);
+    fs::remove_file(invalid).unwrap();
+}
+
+#[test]
+fn policy_violations() {
+    // Position dependent, without canary nor BIND_NOW, and with NX
+    let path = policy("violations", "pie = true\nrelro = full\nnx = true\n\
+                                     canary = true\nrwx = false\n");
+    let exec = common::path("x86_64-exec.elf");
+    let output = elf_parser(&["--policy", path.to_str().unwrap(), &exec]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let violations : Vec<&str> = stderr.lines().collect();
+    assert_eq!(violations, [
+        format!("{}: policy violation: stack canary is required", exec),
+        format!("{}: policy violation: PIE is required", exec),
+        format!("{}: policy violation: RELRO is PartialRelRo, FullRelRo is \
+                 required", exec),
+    ]);
+    fs::remove_file(path).unwrap();
+
+    // Requires GLIBC_2.34 for __libc_start_main
+    let versioned = common::path("x86_64-versioned.elf");
+    let output = elf_parser(&["--max-glibc", "2.17", &versioned]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(),
+               format!("{}: policy violation: GLIBC_2.34 is required by \
+                        __libc_start_main, at most GLIBC_2.17 is allowed\n",
+                       versioned));
+    assert_eq!(exit_code(&["--max-glibc", "2.34.0", &versioned]), 0);
+}
+
+#[test]
+fn scan_directory() {
+    // A tree mixing ELF files at several depths, a file that is not an ELF
+    // and one that only has the ELF magic
+    let root = std::env::temp_dir()
+        .join(format!("elf_parser-scan-{}", std::process::id()));
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    for (file, copy) in [("x86_64-exec.elf", "a/b/exec.elf"),
+                         ("x86_64-pie.elf", "a/pie.elf"),
+                         ("x86_64-lib.elf", "lib.elf")] {
+        fs::copy(common::path(file), root.join(copy)).unwrap();
+    }
+    fs::write(root.join("notes.txt"), "not an ELF\n").unwrap();
+    fs::write(root.join("broken.elf"), b"\x7fELF").unwrap();
+
+    let not_elf = common::path("generate.py");
+    let args = ["--format", "json", root.to_str().unwrap(), &not_elf];
+    let output = elf_parser(&args);
+    // Files that cannot be parsed are errors
+    assert_eq!(output.status.code(), Some(1));
+    let results : serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap();
+    let results = results.as_array().unwrap();
+
+    // Directories are walked in order, files found in them are skipped
+    // unless they start with the ELF magic, files named are not
+    let files : Vec<&str> = results.iter()
+        .map(|result| result["file"].as_str().unwrap())
+        .collect();
+    let found = |file : &str| root.join(file).display().to_string();
+    assert_eq!(files, [found("a/b/exec.elf"), found("a/pie.elf"),
+                       found("broken.elf"), found("lib.elf"), not_elf.clone()]);
+    let errors : Vec<bool> = results.iter()
+        .map(|result| result.get("error").is_some())
+        .collect();
+    assert_eq!(errors, [false, false, true, false, true]);
+    assert_eq!(results[1]["security"]["pie"], "PieExecutable");
+
+    // The output does not depend on the order the threads finish in
+    for _ in 0..3 {
+        assert_eq!(elf_parser(&args).stdout, output.stdout);
+    }
+    let text = elf_parser(&["--no-color", root.to_str().unwrap()]);
+    assert_eq!(text.status.code(), Some(1));
+    let text = String::from_utf8(text.stdout).unwrap();
+    assert!(text.contains("3 files analyzed, 1 could not be parsed"));
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn subcommands() {
+    // Lines each command prints for a corpus file, trailing spaces aside,
+    // and a value of its JSON output
+    let versioned = common::path("x86_64-versioned.elf");
+    let bind_now = common::path("x86_64-bind-now.elf");
+    let cet = common::path("x86_64-cet.elf");
+    let commands : [(&str, &str, &[&str], &str, &str); 8] = [
+        ("header", &versioned, &[
+            "  Type:                              EtDyn",
+            "  Machine:                           Emx86_64",
+        ], "/e_type", "EtDyn"),
+        ("segments", &versioned, &[
+            "There are 4 program headers, starting at offset 64",
+        ], "/0/p_type", "PtLoad"),
+        ("sections", &versioned, &[
+            "There are 10 section headers, starting at offset 0x5a0:",
+        ], "/2/name", ".dynsym"),
+        ("symbols", &versioned, &[
+            "Symbol table '.dynsym' contains 10 entries:",
+        ], "/1/name", "puts"),
+        ("dyn", &versioned, &[
+            "  0x0000000000000001 DtNeeded             libc.so.6",
+            "  0x000000000000000e DtSoname             libgreet.so.2",
+        ], "/entries/2/string", "libgreet.so.2"),
+        ("relocs", &bind_now, &[
+            "  0000000000400238 R_X86_64_JUMP_SLOT            1 puts + 0",
+            "  0000000000400240 R_X86_64_JUMP_SLOT            2 exit + 0",
+        ], "/0/relocs/1/symbol", "exit"),
+        ("notes", &cet, &[
+            "    Build ID: 0102030405060708090a0b0c0d0e0f1011121314",
+        ], "/0/name", "GNU"),
+        ("versions", &versioned, &[
+            "  Index: 3   Flags: none  Name: LIBGREET_2.0 (parents: \
+             LIBGREET_1.0)",
+        ], "/definitions/2/name", "LIBGREET_2.0"),
+    ];
+
+    for &(command, file, lines, pointer, value) in &commands {
+        let output = elf_parser(&["--no-color", command, file]);
+        assert_eq!(output.status.code(), Some(0), "{}", command);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let printed : Vec<&str> = stdout.lines().map(str::trim_end).collect();
+        for line in lines {
+            assert!(printed.contains(line), "{}: no line {:?}", command, line);
+        }
+
+        let output = elf_parser(&["--format", "json", command, file]);
+        assert_eq!(output.status.code(), Some(0), "{}", command);
+        let json : serde_json::Value = serde_json::from_slice(&output.stdout)
+            .unwrap_or_else(|e| panic!("{}: {}", command, e));
+        assert_eq!(json.pointer(pointer).and_then(|v| v.as_str()), Some(value),
+                   "{}", command);
+    }
+}
+
+#[test]
+fn json_several_files() {
+    // A single array of objects, with no header naming the files
+    let lib = common::path("x86_64-lib.elf");
+    let not_elf = common::path("generate.py");
+    let output = elf_parser(&["--format", "json", "dyn", &lib, &not_elf]);
+    assert_eq!(output.status.code(), Some(1));
+    let json : serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap();
+
+    let files = json.as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0]["file"], lib.as_str());
+    assert_eq!(files[0]["dyn"]["entries"][1]["string"], "libgreet.so.1");
+    assert_eq!(files[1]["file"], not_elf.as_str());
+    assert!(files[1]["error"].as_str().unwrap().starts_with("bad magic"));
+    assert!(files[1].get("dyn").is_none());
+}
+
+#[test]
+#[cfg(not(feature = "demangle"))]
+fn demangle_without_feature() {
+    let exec = common::path("x86_64-exec.elf");
+    let output = elf_parser(&["--demangle", "symbols", &exec]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(),
+               "--demangle: built without the `demangle` feature\n");
+}
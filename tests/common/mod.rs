@@ -0,0 +1,26 @@
+//! Fixtures shared by the tests: the files of `tests/corpus`, generated by
+//! `tests/corpus/generate.py`
+
+// Each test file only uses some of the helpers
+#![allow(dead_code)]
+
+use elf_parser::elf::*;
+
+/// Directory holding the corpus
+pub const CORPUS : &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/corpus");
+
+/// Path of the corpus file `name`
+pub fn path(name : &str) -> String {
+    format!("{}/{}", CORPUS, name)
+}
+
+/// Loads the corpus file `name`
+pub fn load(name : &str) -> Elf<'static> {
+    Elf::load(path(name))
+        .unwrap_or_else(|e| panic!("{}: {}", name, e))
+}
+
+/// Mitigations of the corpus file `name`
+pub fn security(name : &str) -> SecurityOptions {
+    SecurityOptions::get_options(&load(name)).unwrap()
+}
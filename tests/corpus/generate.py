@@ -0,0 +1,609 @@
+#!/usr/bin/env python3
+"""Generates the minimal ELF files of the test corpus.
+
+The files built by `build` hold an ELF header, a PT_LOAD, PT_GNU_STACK and
+PT_GNU_RELRO program header, and the sections .text, .symtab, .strtab and
+.shstrtab. The other files are laid out by `write_elf` from a list of
+sections and of the segments covering them.
+"""
+import struct
+
+SECTIONS = [b"", b".text", b".symtab", b".strtab", b".shstrtab"]
+
+
+def build(path, bits, endian, machine, entry):
+    e = ">" if endian == "msb" else "<"
+    is64 = bits == 64
+    ehsize = 64 if is64 else 52
+    phentsize = 56 if is64 else 32
+    shentsize = 64 if is64 else 40
+    phnum = 3
+    shnum = len(SECTIONS)
+
+    text = b"\x00" * 16
+    shstrtab = b"\x00" + b"".join(n + b"\x00" for n in SECTIONS[1:])
+    strtab = b"\x00main\x00__stack_chk_fail\x00"
+    base = 0x400000
+
+    def sym(name, value, size, info, shndx):
+        if is64:
+            return struct.pack(e + "IBBHQQ", name, info, 0, shndx, value, size)
+        return struct.pack(e + "IIIBBH", name, value, size, info, 0, shndx)
+
+    phoff = ehsize
+    text_off = phoff + phnum * phentsize
+    shstrtab_off = text_off + len(text)
+    strtab_off = shstrtab_off + len(shstrtab)
+    symtab_off = strtab_off + len(strtab)
+    symtab = sym(0, 0, 0, 0, 0) + sym(1, base + text_off, 16, 0x12, 1)
+    symtab += sym(6, 0, 0, 0x12, 0)
+    shoff = symtab_off + len(symtab)
+    size = shoff + shnum * shentsize
+
+    ident = b"\x7fELF" + bytes([2 if is64 else 1, 2 if e == ">" else 1, 1, 0])
+    ident += b"\x00" * 8
+    word = "Q" if is64 else "I"
+    header = ident + struct.pack(
+        e + "HHI" + word * 3 + "IHHHHHH",
+        2, machine, 1, entry, phoff, shoff, 0,
+        ehsize, phentsize, phnum, shentsize, shnum, 4)
+
+    def phdr(p_type, flags, offset, vaddr, filesz, align):
+        if is64:
+            return struct.pack(e + "IIQQQQQQ", p_type, flags, offset, vaddr,
+                               vaddr, filesz, filesz, align)
+        return struct.pack(e + "IIIIIIII", p_type, offset, vaddr, vaddr,
+                           filesz, filesz, flags, align)
+
+    phdrs = phdr(1, 5, 0, base, size, 0x1000)
+    phdrs += phdr(0x6474e551, 6, 0, 0, 0, 0x10)
+    phdrs += phdr(0x6474e552, 4, text_off, base + text_off, len(text), 1)
+
+    def shdr(name, sh_type, flags, addr, offset, size, link, entsize, info=0):
+        name_off = shstrtab.index(SECTIONS[name] + b"\x00") if name else 0
+        if is64:
+            return struct.pack(e + "IIQQQQIIQQ", name_off, sh_type, flags,
+                               addr, offset, size, link, info, 1, entsize)
+        return struct.pack(e + "IIIIIIIIII", name_off, sh_type, flags, addr,
+                           offset, size, link, info, 1, entsize)
+
+    shdrs = shdr(0, 0, 0, 0, 0, 0, 0, 0)
+    shdrs += shdr(1, 1, 6, base + text_off, text_off, len(text), 0, 0)
+    shdrs += shdr(2, 2, 0, 0, symtab_off, len(symtab), 3,
+                   len(symtab) // 3, info=1)
+    shdrs += shdr(3, 3, 0, 0, strtab_off, len(strtab), 0, 0)
+    shdrs += shdr(4, 3, 0, 0, shstrtab_off, len(shstrtab), 0, 0)
+
+    data = header + phdrs + text + shstrtab + strtab + symtab + shdrs
+    assert len(data) == size
+    with open(path, "wb") as f:
+        f.write(data)
+
+
+class Section:
+    """A section of a file laid out by `write_elf`. `data` is either bytes or
+    a function building them from the address of the other sections, which
+    must not change their size. `link` is the name of the linked section."""
+
+    def __init__(self, name, sh_type, data, flags=0, align=1, link=None,
+                 info=0, entsize=0):
+        self.name = name
+        self.sh_type = sh_type
+        self.data = data
+        self.flags = flags
+        self.align = align
+        self.link = link
+        self.info = info
+        self.entsize = entsize
+
+    def content(self, addr):
+        return self.data(addr) if callable(self.data) else self.data
+
+
+SHF_ALLOC = 0x2
+
+
+def align_up(value, align):
+    return (value + align - 1) // align * align
+
+
+def write_elf(path, bits, endian, machine, e_type, sections, segments,
+              entry=0, base=0x400000, section_headers=True):
+    """Lays out `sections` after the headers, each at the address `base`
+    plus its offset when it is allocated, then the section header table
+    unless `section_headers` is false, as in files stripped of it.
+    `segments` are `(p_type, p_flags, names, p_align)` tuples covering the
+    named sections, the whole file for an empty list of names, or nothing
+    for `None`."""
+    e = ">" if endian == "msb" else "<"
+    is64 = bits == 64
+    ehsize = 64 if is64 else 52
+    phentsize = 56 if is64 else 32
+    shentsize = 64 if is64 else 40
+    word = "Q" if is64 else "I"
+
+    names = [b""] + [s.name for s in sections] + [b".shstrtab"]
+    shstrtab = b"\x00" + b"".join(n + b"\x00" for n in names[1:])
+    sections = sections + [Section(b".shstrtab", 3, shstrtab)]
+
+    # Sizes do not depend on the addresses, a first pass gives the offsets
+    offsets = {}
+    pos = ehsize + len(segments) * phentsize
+    for s in sections:
+        pos = align_up(pos, s.align)
+        offsets[s.name] = pos
+        pos += len(s.content(lambda name: 0))
+
+    def addr(name):
+        s = next(s for s in sections if s.name == name)
+        return base + offsets[name] if s.flags & SHF_ALLOC else 0
+
+    contents = {s.name: s.content(addr) for s in sections}
+    shoff = align_up(pos, 8)
+    size = shoff + len(names) * shentsize
+    if not section_headers:
+        shoff, size = 0, pos
+
+    ident = b"\x7fELF" + bytes([2 if is64 else 1, 2 if e == ">" else 1, 1, 0])
+    ident += b"\x00" * 8
+    data = bytearray(ident + struct.pack(
+        e + "HHI" + word * 3 + "IHHHHHH",
+        e_type, machine, 1, entry, ehsize if segments else 0, shoff, 0,
+        ehsize, phentsize, len(segments), shentsize,
+        len(names) if section_headers else 0,
+        len(names) - 1 if section_headers else 0))
+
+    for p_type, flags, covered, align in segments:
+        if covered:
+            offset = offsets[covered[0]]
+            filesz = offsets[covered[-1]] + len(contents[covered[-1]]) - offset
+            vaddr = addr(covered[0])
+        elif covered is None:
+            offset, filesz, vaddr = 0, 0, 0
+        else:
+            offset, filesz, vaddr = 0, size, base
+        if is64:
+            data += struct.pack(e + "IIQQQQQQ", p_type, flags, offset, vaddr,
+                                vaddr, filesz, filesz, align)
+        else:
+            data += struct.pack(e + "IIIIIIII", p_type, offset, vaddr, vaddr,
+                                filesz, filesz, flags, align)
+
+    for s in sections:
+        data += b"\x00" * (offsets[s.name] - len(data)) + contents[s.name]
+    if not section_headers:
+        assert len(data) == size
+        with open(path, "wb") as f:
+            f.write(data)
+        return
+    data += b"\x00" * (shoff - len(data))
+
+    index = {name: i for i, name in enumerate(names)}
+    data += b"\x00" * shentsize
+    for s in sections:
+        fields = (shstrtab.index(s.name + b"\x00"), s.sh_type, s.flags,
+                  addr(s.name), offsets[s.name], len(contents[s.name]),
+                  index[s.link] if s.link else 0, s.info, s.align, s.entsize)
+        if is64:
+            data += struct.pack(e + "IIQQQQIIQQ", *fields)
+        else:
+            data += struct.pack(e + "IIIIIIIIII", *fields)
+
+    assert len(data) == size
+    with open(path, "wb") as f:
+        f.write(data)
+
+
+def note(e, name, n_type, desc, align):
+    """A note whose descriptor and end are aligned to `align`"""
+    name += b"\x00"
+    pad = lambda b: b + b"\x00" * (align_up(len(b), align) - len(b))
+    return pad(pad(struct.pack(e + "III", len(name), len(desc), n_type)
+                   + name) + desc)
+
+
+def properties(e, props, align):
+    """Descriptor of a NT_GNU_PROPERTY_TYPE_0 note, each property padded to
+    the size of an address"""
+    desc = b""
+    for pr_type, pr_data in props:
+        desc += struct.pack(e + "II", pr_type, len(pr_data)) + pr_data
+        desc += b"\x00" * (align_up(len(desc), align) - len(desc))
+    return desc
+
+
+def build_notes(path, bits, machine, props, gnu_property=True):
+    """A file with a .note.gnu.property section padded to the size of an
+    address, and a build-id and ABI tag padded to 4 bytes"""
+    e = "<"
+    align = bits // 8
+    desc = properties(e, props, align)
+    build_id = bytes(range(1, 21))
+    sections = [
+        Section(b".note.gnu.property", 7, note(e, b"GNU", 5, desc, align),
+                flags=SHF_ALLOC, align=align),
+        Section(b".note.gnu.build-id", 7, note(e, b"GNU", 3, build_id, 4),
+                flags=SHF_ALLOC, align=4),
+        Section(b".note.ABI-tag", 7,
+                note(e, b"GNU", 1, struct.pack(e + "IIII", 0, 3, 2, 0), 4),
+                flags=SHF_ALLOC, align=4),
+        Section(b".text", 1, b"\x00" * 16, flags=SHF_ALLOC | 0x4, align=16),
+    ]
+    segments = [
+        (1, 5, [], 0x1000),
+        (4, 4, [b".note.gnu.property"], align),
+        (4, 4, [b".note.gnu.build-id", b".note.ABI-tag"], 4),
+        (0x6474e551, 6, None, 0x10),
+    ]
+    if gnu_property:
+        segments.append((0x6474e553, 4, [b".note.gnu.property"], align))
+    write_elf(path, bits, "lsb", machine, 2, sections, segments)
+
+
+def sysv_hash(name):
+    h = 0
+    for c in name.encode():
+        h = (h << 4) + c
+        g = h & 0xf0000000
+        h ^= g >> 24
+        h &= ~g & 0xffffffff
+    return h
+
+
+def gnu_hash(name):
+    h = 5381
+    for c in name.encode():
+        h = (h * 33 + c) & 0xffffffff
+    return h
+
+
+# Few buckets, so that the tables have chains
+NBUCKETS = 3
+BLOOM_SHIFT = 5
+
+
+def sysv_hash_table(e, names):
+    """SHT_HASH table of the symbols named `names`, the first one being the
+    null symbol"""
+    buckets = [0] * NBUCKETS
+    chains = [0] * len(names)
+    for index, name in enumerate(names[1:], 1):
+        bucket = sysv_hash(name) % NBUCKETS
+        chains[index] = buckets[bucket]
+        buckets[bucket] = index
+    return struct.pack(e + "II", NBUCKETS, len(names)) \
+        + struct.pack(e + "I" * (NBUCKETS + len(names)), *(buckets + chains))
+
+
+def gnu_hash_table(e, bits, names, symoffset):
+    """SHT_GNU_HASH table of the symbols named `names` from `symoffset`,
+    which are sorted by bucket, with a bloom filter of one word"""
+    hashes = [gnu_hash(name) for name in names[symoffset:]]
+    bloom = 0
+    for h in hashes:
+        bloom |= 1 << (h % bits)
+        bloom |= 1 << ((h >> BLOOM_SHIFT) % bits)
+    buckets = [0] * NBUCKETS
+    chain = []
+    for i, h in enumerate(hashes):
+        if buckets[h % NBUCKETS] == 0:
+            buckets[h % NBUCKETS] = symoffset + i
+        last = i + 1 == len(hashes) or hashes[i + 1] % NBUCKETS != h % NBUCKETS
+        chain.append((h & ~1) | last)
+    word = "Q" if bits == 64 else "I"
+    return struct.pack(e + "IIII", NBUCKETS, symoffset, 1, BLOOM_SHIFT) \
+        + struct.pack(e + word, bloom) \
+        + struct.pack(e + "I" * (NBUCKETS + len(chain)), *(buckets + chain))
+
+
+def split_symbol(symbol):
+    """Splits `name@VERSION` or `name@@VERSION`, the default version, into
+    the name, the version and whether the version is not the default one"""
+    name, _, version = symbol.partition("@")
+    hidden = bool(version) and not version.startswith("@")
+    return name, version.lstrip("@"), hidden
+
+
+def version_definitions(e, verdef, soname, name_offsets):
+    """`.gnu.version_d` of the base version, named `soname`, then of the
+    `(name, parents)` of `verdef`, numbered from 2"""
+    data = b""
+    entries = [(soname, [], 1)] + [(n, p, 0) for n, p in verdef]
+    for index, (name, parents, flags) in enumerate(entries, 1):
+        names = [name] + list(parents)
+        next_def = 0 if index == len(entries) else 20 + 8 * len(names)
+        data += struct.pack(e + "HHHHIII", 1, flags, index, len(names),
+                            sysv_hash(name), 20, next_def)
+        for i, aux in enumerate(names):
+            next_aux = 0 if i + 1 == len(names) else 8
+            data += struct.pack(e + "II", name_offsets[aux], next_aux)
+    return data
+
+
+def version_requirements(e, verneed, first_index, name_offsets):
+    """`.gnu.version_r` of the `(file, versions)` of `verneed`, whose
+    versions are numbered from `first_index`. A version ending with `?` is
+    weak."""
+    data = b""
+    index = first_index
+    for i, (file, versions) in enumerate(verneed):
+        next_need = 0 if i + 1 == len(verneed) else 16 + 16 * len(versions)
+        data += struct.pack(e + "HHIII", 1, len(versions), name_offsets[file],
+                            16, next_need)
+        for j, version in enumerate(versions):
+            name = version.rstrip("?")
+            flags = 2 if version.endswith("?") else 0
+            next_aux = 0 if j + 1 == len(versions) else 16
+            data += struct.pack(e + "IHHII", sysv_hash(name), flags, index,
+                                name_offsets[name], next_aux)
+            index += 1
+    return data
+
+
+def build_dynamic(path, bits, e_type, imports, exports=(), interp=None,
+                  soname=None, rpath=None, runpath=None, flags=0, flags_1=0,
+                  hashes=(), section_headers=True, verdef=(), verneed=(),
+                  jump_slots=False):
+    """A dynamically linked x86 file importing `imports` from libc.so.6 and
+    exporting functions of 16 bytes named `exports`, with hash tables of
+    the `hashes` styles, `gnu` or `sysv`. With `jump_slots`, a 64 bits file
+    has a `.rela.plt` filling one `.got` entry per import.
+
+    Files defining the `(name, parents)` versions of `verdef` or requiring
+    the `(file, versions)` of `verneed` are versioned, their symbols are
+    then named `name@VERSION`, or `name@@VERSION` for a default version."""
+    e = "<"
+    is64 = bits == 64
+    machine = 62 if is64 else 3
+    word = "Q" if is64 else "I"
+    symsize = 24 if is64 else 16
+    # The GNU hash table only covers the exports, sorted by bucket
+    if "gnu" in hashes:
+        exports = sorted(exports,
+                         key=lambda n: gnu_hash(split_symbol(n)[0]) % NBUCKETS)
+    symbols = [split_symbol(n) for n in [""] + list(imports) + list(exports)]
+    symoffset = 1 + len(imports)
+    # Only definitions can be hidden, imports name the version they need
+    symbols = [(name, version, hidden and i >= symoffset)
+               for i, (name, version, hidden) in enumerate(symbols)]
+    versioned = bool(verdef or verneed)
+
+    names = [name for name, _, _ in symbols[1:]] + ["libc.so.6"]
+    for name in [soname, rpath, runpath]:
+        if name is not None:
+            names.append(name)
+    for name, parents in verdef:
+        names += [name] + list(parents)
+    for file, versions in verneed:
+        names += [file] + [version.rstrip("?") for version in versions]
+    dynstr = b"\x00"
+    name_offsets = {}
+    for name in names:
+        if name not in name_offsets:
+            name_offsets[name] = len(dynstr)
+            dynstr += name.encode() + b"\x00"
+
+    # Index of each version, 1 being the base version when defining some
+    version_index = {}
+    for index, (name, _) in enumerate(verdef, 2):
+        version_index[name] = index
+    index = 2 + len(verdef) if verdef else 2
+    for _, versions in verneed:
+        for version in versions:
+            version_index[version.rstrip("?")] = index
+            index += 1
+
+    def sym(name, value, size, info, shndx):
+        if is64:
+            return struct.pack(e + "IBBHQQ", name, info, 0, shndx, value, size)
+        return struct.pack(e + "IIIBBH", name, value, size, info, 0, shndx)
+
+    def dynsym(addr):
+        data = sym(0, 0, 0, 0, 0)
+        for name, _, _ in symbols[1:symoffset]:
+            data += sym(name_offsets[name], 0, 0, 0x12, 0)
+        for i, (name, _, _) in enumerate(symbols[symoffset:]):
+            data += sym(name_offsets[name], addr(b".text") + 16 * i, 16, 0x12,
+                        text_index)
+        return data
+
+    def versym():
+        indices = [0] + [version_index.get(version, 1) | (hidden << 15)
+                         for _, version, hidden in symbols[1:]]
+        return struct.pack(e + "H" * len(indices), *indices)
+
+    def rela_plt(addr):
+        # R_X86_64_JUMP_SLOT after the 3 entries reserved for the loader
+        return b"".join(struct.pack(e + "QQq", addr(b".got") + 8 * (3 + i),
+                                    (1 + i) << 32 | 7, 0)
+                        for i in range(len(imports)))
+
+    def dynamic(addr):
+        entries = [(1, name_offsets["libc.so.6"])]
+        entries += [(1, name_offsets[file]) for file, _ in verneed
+                    if file != "libc.so.6"]
+        if soname:
+            entries.append((14, name_offsets[soname]))
+        if rpath is not None:
+            entries.append((15, name_offsets[rpath]))
+        if runpath is not None:
+            entries.append((29, name_offsets[runpath]))
+        entries += [(5, addr(b".dynstr")), (6, addr(b".dynsym")),
+                    (10, len(dynstr)), (11, symsize)]
+        if "gnu" in hashes:
+            entries.append((0x6ffffef5, addr(b".gnu.hash")))
+        if "sysv" in hashes:
+            entries.append((4, addr(b".hash")))
+        if versioned:
+            entries.append((0x6ffffff0, addr(b".gnu.version")))
+        if verdef:
+            entries += [(0x6ffffffc, addr(b".gnu.version_d")),
+                        (0x6ffffffd, 1 + len(verdef))]
+        if verneed:
+            entries += [(0x6ffffffe, addr(b".gnu.version_r")),
+                        (0x6fffffff, len(verneed))]
+        if jump_slots:
+            entries += [(23, addr(b".rela.plt")), (2, 24 * len(imports)),
+                        (20, 7)]
+        if flags:
+            entries.append((30, flags))
+        if flags_1:
+            entries.append((0x6ffffffb, flags_1))
+        entries.append((0, 0))
+        return b"".join(struct.pack(e + word * 2, tag, val)
+                        for tag, val in entries)
+
+    sections = []
+    segments = []
+    if interp:
+        sections.append(Section(b".interp", 1, interp.encode() + b"\x00",
+                                flags=SHF_ALLOC))
+        segments.append((3, 4, [b".interp"], 1))
+    if "gnu" in hashes:
+        sections.append(Section(b".gnu.hash", 0x6ffffff6,
+                                gnu_hash_table(e, bits, [n for n, _, _ in symbols],
+                                               symoffset),
+                                flags=SHF_ALLOC, align=8, link=b".dynsym"))
+    if "sysv" in hashes:
+        sections.append(Section(b".hash", 5,
+                                sysv_hash_table(e, [n for n, _, _ in symbols]),
+                                flags=SHF_ALLOC, align=8, link=b".dynsym",
+                                entsize=4))
+    sections += [
+        Section(b".dynsym", 11, dynsym, flags=SHF_ALLOC, align=8,
+                link=b".dynstr", info=1, entsize=symsize),
+        Section(b".dynstr", 3, dynstr, flags=SHF_ALLOC),
+    ]
+    if versioned:
+        sections.append(Section(b".gnu.version", 0x6fffffff, versym(),
+                                flags=SHF_ALLOC, align=2, link=b".dynsym",
+                                entsize=2))
+    if verdef:
+        sections.append(Section(b".gnu.version_d", 0x6ffffffd,
+                                version_definitions(e, verdef, soname,
+                                                    name_offsets),
+                                flags=SHF_ALLOC, align=8, link=b".dynstr",
+                                info=1 + len(verdef)))
+    if verneed:
+        first_index = 2 + len(verdef) if verdef else 2
+        sections.append(Section(b".gnu.version_r", 0x6ffffffe,
+                                version_requirements(e, verneed, first_index,
+                                                     name_offsets),
+                                flags=SHF_ALLOC, align=8, link=b".dynstr",
+                                info=len(verneed)))
+    if jump_slots:
+        sections.append(Section(b".rela.plt", 4, rela_plt, flags=SHF_ALLOC,
+                                align=8, link=b".dynsym", entsize=24))
+    sections.append(Section(b".text", 1, b"\x00" * max(16, 16 * len(exports)),
+                            flags=SHF_ALLOC | 0x4, align=16))
+    if jump_slots:
+        sections.append(Section(b".got", 1, b"\x00" * 8 * (3 + len(imports)),
+                                flags=SHF_ALLOC | 0x1, align=8))
+    sections += [
+        Section(b".dynamic", 6, dynamic, flags=SHF_ALLOC | 0x1, align=8,
+                link=b".dynstr", entsize=2 * (bits // 8)),
+    ]
+    # The exports are defined in .text, after the null section
+    text_index = 1 + [s.name for s in sections].index(b".text")
+    segments += [
+        (1, 5, [], 0x1000),
+        (2, 6, [b".dynamic"], 8),
+        (0x6474e551, 6, None, 0x10),
+        (0x6474e552, 4, [b".dynamic"], 1),
+    ]
+    write_elf(path, bits, "lsb", machine, e_type, sections, segments,
+              section_headers=section_headers)
+
+
+if __name__ == "__main__":
+    build("mips32-msb.elf", 32, "msb", 8, 0x400100)
+    build("ppc64-msb.elf", 64, "msb", 21, 0x10000400)
+    build("x86_64-lsb.elf", 64, "lsb", 62, 0x401000)
+
+    # GNU_PROPERTY_X86_ISA_1_NEEDED is padded to 8 bytes before the
+    # GNU_PROPERTY_X86_FEATURE_1_AND with IBT and SHSTK
+    build_notes("x86_64-cet.elf", 64, 62, [
+        (0xc0008002, struct.pack("<I", 1)),
+        (0xc0000002, struct.pack("<I", 3)),
+    ])
+    # GNU_PROPERTY_AARCH64_FEATURE_1_AND with BTI and PAC
+    build_notes("aarch64-bti.elf", 64, 183, [
+        (0xc0000000, struct.pack("<I", 3)),
+    ])
+    # A 4 bytes GNU_PROPERTY_STACK_SIZE and IBT and SHSTK, found through the
+    # section as there is no PT_GNU_PROPERTY
+    build_notes("i386-cet.elf", 32, 3, [
+        (1, struct.pack("<I", 0x100000)),
+        (0xc0000002, struct.pack("<I", 3)),
+    ], gnu_property=False)
+
+    # A PIE executable with fortified and unfortified imports.
+    # __fdelt_chk has no unfortified counterpart, __longjmp_chk has one.
+    pie_imports = [
+        "__stack_chk_fail", "__memcpy_chk", "__fdelt_chk", "__longjmp_chk",
+        "strcpy", "printf", "memcpy",
+    ]
+    build_dynamic("x86_64-pie.elf", 64, 3, pie_imports,
+                  interp="/lib64/ld-linux-x86-64.so.2", flags_1=0x08000001)
+    # The same without section headers, its imports are found through the
+    # dynamic array and the GNU hash table
+    build_dynamic("x86_64-pie-stripped.elf", 64, 3, pie_imports,
+                  interp="/lib64/ld-linux-x86-64.so.2", flags_1=0x08000001,
+                  hashes=("gnu",), section_headers=False)
+    # The other ways a file is placed in memory: a position dependent
+    # executable, a PIE from a linker that did not set DF_1_PIE, a static
+    # PIE, and a library requesting an interpreter as libc.so.6 does
+    build_dynamic("x86_64-exec.elf", 64, 2, ["puts"],
+                  interp="/lib64/ld-linux-x86-64.so.2")
+    build_dynamic("x86_64-legacy-pie.elf", 64, 3, ["puts"],
+                  interp="/lib64/ld-linux-x86-64.so.2")
+    # A position dependent executable binding now through DT_FLAGS rather
+    # than DT_FLAGS_1, whose imports are relocated in the GOT
+    build_dynamic("x86_64-bind-now.elf", 64, 2, ["puts", "exit"],
+                  interp="/lib64/ld-linux-x86-64.so.2", flags=0x8,
+                  jump_slots=True)
+    build_dynamic("x86_64-static-pie.elf", 64, 3, [], flags_1=0x08000001)
+    build_dynamic("x86_64-lib.elf", 64, 3, ["puts"], exports=["greet"],
+                  interp="/lib64/ld-linux-x86-64.so.2", soname="libgreet.so.1",
+                  rpath="$ORIGIN/../lib")
+    # Library search paths in world-writable directories, leaving $ORIGIN,
+    # relative or harmless once normalized
+    build_dynamic("x86_64-rpath.elf", 64, 2, ["puts"],
+                  interp="/lib64/ld-linux-x86-64.so.2",
+                  rpath="/tmp/build/lib:$ORIGIN/../../../tmp:/var/tmp/../lib",
+                  runpath="/dev/shm:${ORIGIN}/lib/../plugins:lib:/tmpfs/lib")
+
+    # Libraries whose exports are looked up through both hash tables, only
+    # the SysV one, or the dynamic array as the section headers are stripped
+    words = ["alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf"]
+    build_dynamic("x86_64-hash.elf", 64, 3, ["puts"], exports=words,
+                  soname="libhash.so.1", hashes=("gnu", "sysv"))
+    build_dynamic("i386-hash.elf", 32, 3, ["puts"], exports=words,
+                  soname="libhash.so.1", hashes=("gnu",))
+    build_dynamic("x86_64-sysv-hash.elf", 64, 3, ["puts"], exports=words,
+                  soname="libhash.so.1", hashes=("sysv",))
+    build_dynamic("x86_64-hash-stripped.elf", 64, 3, ["puts"], exports=words,
+                  soname="libhash.so.1", hashes=("gnu", "sysv"),
+                  section_headers=False)
+    build_dynamic("x86_64-sysv-hash-stripped.elf", 64, 3, ["puts"],
+                  exports=words, soname="libhash.so.1", hashes=("sysv",),
+                  section_headers=False)
+
+    # A library defining two versions of greet, the hidden LIBGREET_1.0 one
+    # and the default LIBGREET_2.0 one, and requiring versions of libc and
+    # libgcc_s, one of them weak. The copy without section headers finds the
+    # versions through the dynamic array.
+    for path in ["x86_64-versioned.elf", "x86_64-versioned-stripped.elf"]:
+        build_dynamic(path, 64, 3, [
+            "puts@GLIBC_2.2.5", "memcpy@GLIBC_2.14",
+            "__libc_start_main@GLIBC_2.34", "_dl_catch_error@GLIBC_PRIVATE",
+            "_Unwind_Resume@GCC_3.0", "abort",
+        ], exports=[
+            "greet@LIBGREET_1.0", "greet@@LIBGREET_2.0",
+            "farewell@@LIBGREET_1.0",
+        ], soname="libgreet.so.2", hashes=("gnu",),
+            verdef=[("LIBGREET_1.0", []), ("LIBGREET_2.0", ["LIBGREET_1.0"])],
+            verneed=[("libc.so.6", ["GLIBC_2.2.5", "GLIBC_2.14", "GLIBC_2.34",
+                                    "GLIBC_PRIVATE", "GLIBC_2.4?"]),
+                     ("libgcc_s.so.1", ["GCC_3.0", "GCC_4.2.0"])],
+            section_headers=path == "x86_64-versioned.elf")
@@ -0,0 +1,46 @@
+//! Reads the dynamic arrays of the files of `tests/corpus`
+
+use elf_parser::elf::*;
+
+mod common;
+
+#[test]
+fn library_entries() {
+    let dynamic = common::load("x86_64-lib.elf").dynamic().unwrap().unwrap();
+
+    assert_eq!(dynamic.needed(), ["libc.so.6"]);
+    assert_eq!(dynamic.soname(), Some("libgreet.so.1"));
+    assert_eq!(dynamic.rpath(), Some("$ORIGIN/../lib"));
+    assert_eq!(dynamic.runpath(), None);
+    assert_eq!(dynamic.get(DynTag::DtSymEnt), Some(24));
+
+    // Entries keep the order of the array, strings only for string tags
+    let tags : Vec<DynTag> = dynamic.entries.iter().map(|e| e.tag).collect();
+    assert_eq!(&tags[..3], [DynTag::DtNeeded, DynTag::DtSoname, 
+                            DynTag::DtRpath]);
+    assert!(dynamic.entries.iter()
+            .all(|e| e.string.is_some() == DynEntry::has_string(e.tag)));
+}
+
+#[test]
+fn static_file() {
+    assert!(common::load("x86_64-lsb.elf").dynamic().unwrap().is_none());
+}
+
+#[test]
+fn string_table_past_end() {
+    // A DT_STRSZ running past the end of the file is cut at the end
+    let mut data = std::fs::read(common::path("x86_64-lib.elf")).unwrap();
+    let elf = common::load("x86_64-lib.elf");
+    let phdr = elf.segments().iter()
+        .find(|phdr| phdr.p_type == PType::PtDynamic).unwrap();
+    let strsz = (phdr.p_offset as usize..)
+        .step_by(16)
+        .find(|&offset| data[offset] == 10).unwrap();
+    data[strsz + 8..strsz + 16].copy_from_slice(&0x100000u64.to_le_bytes());
+
+    let dynamic = Elf::parse(&data).unwrap().dynamic().unwrap().unwrap();
+    assert_eq!(dynamic.get(DynTag::DtStrSz), Some(0x100000));
+    assert_eq!(dynamic.needed(), ["libc.so.6"]);
+    assert_eq!(dynamic.soname(), Some("libgreet.so.1"));
+}
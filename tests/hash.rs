@@ -0,0 +1,85 @@
+//! Looks the exports of the libraries of `tests/corpus` up through their hash
+//! tables, which have 3 buckets so that most chains hold several symbols
+
+mod common;
+
+const EXPORTS : [&str; 7] = ["alpha", "bravo", "charlie", "delta", "echo",
+                             "foxtrot", "golf"];
+
+/// Checks that every export is found at the address the symbol table of
+/// `reference` gives, and that imports and unknown names are not found
+fn check_lookups(file : &str, reference : &str) {
+    let elf = common::load(file);
+    let reference = common::load(reference);
+    let exports = reference.exports().unwrap();
+    assert_eq!(exports.len(), EXPORTS.len());
+
+    for name in EXPORTS {
+        let symbol = elf.lookup_dynamic_symbol(name).unwrap()
+            .unwrap_or_else(|| panic!("{}: {} not found", file, name));
+        let export = exports.iter().find(|sym| sym.name == name).unwrap();
+        assert_eq!((symbol.name.as_str(), symbol.value, symbol.size),
+                   (name, export.value, 16));
+    }
+    assert!(elf.lookup_dynamic_symbol("puts").unwrap().is_none());
+    assert!(elf.lookup_dynamic_symbol("hotel").unwrap().is_none());
+    assert!(elf.lookup_dynamic_symbol("").unwrap().is_none());
+}
+
+#[test]
+fn lookup_gnu_hash() {
+    check_lookups("x86_64-hash.elf", "x86_64-hash.elf");
+    check_lookups("i386-hash.elf", "i386-hash.elf");
+
+    let elf = common::load("x86_64-hash.elf");
+    let shdr = elf.section_by_name(".gnu.hash").unwrap();
+    let table = elf.gnu_hash_table(shdr).unwrap();
+    assert_eq!((table.buckets.len(), table.symoffset), (3, 2));
+    assert_eq!(table.chain.len(), EXPORTS.len());
+}
+
+#[test]
+fn lookup_sysv_hash() {
+    check_lookups("x86_64-sysv-hash.elf", "x86_64-sysv-hash.elf");
+
+    let elf = common::load("x86_64-sysv-hash.elf");
+    let shdr = elf.section_by_name(".hash").unwrap();
+    let table = elf.sysv_hash_table(shdr).unwrap();
+    assert_eq!(table.buckets.len(), 3);
+    // The null symbol, puts and the exports
+    assert_eq!(table.chains.len(), 2 + EXPORTS.len());
+    // SysV chains are not filtered by hash, the whole bucket is returned
+    let candidates = table.candidates("puts");
+    assert!(candidates.len() > 1 && candidates.contains(&1));
+}
+
+#[test]
+fn lookup_without_section_headers() {
+    // The tables are found through DT_GNU_HASH or DT_HASH, DT_SYMTAB and
+    // DT_STRTAB
+    let elf = common::load("x86_64-hash-stripped.elf");
+    assert!(elf.sections().is_empty());
+    check_lookups("x86_64-hash-stripped.elf", "x86_64-hash.elf");
+    let names = |elf : &elf_parser::elf::Elf| -> Vec<String> {
+        elf.dynamic_symbols().unwrap().into_iter().map(|s| s.name).collect()
+    };
+    assert_eq!(names(&elf), names(&common::load("x86_64-hash.elf")));
+    check_lookups("x86_64-sysv-hash-stripped.elf", "x86_64-sysv-hash.elf");
+}
+
+#[test]
+fn corrupted_bloom_shift() {
+    // A shift of 40 bits does not fit the 32 bits hashes
+    let mut data = std::fs::read(common::path("x86_64-hash.elf")).unwrap();
+    let offset = common::load("x86_64-hash.elf")
+        .section_by_name(".gnu.hash").unwrap()
+        .sh_offset as usize;
+    data[offset + 12..offset + 16].copy_from_slice(&40u32.to_le_bytes());
+
+    let elf = elf_parser::elf::Elf::parse(&data).unwrap();
+    let shdr = elf.section_by_name(".gnu.hash").unwrap();
+    assert_eq!(elf.gnu_hash_table(shdr).unwrap().bloom_shift, 40);
+    for name in EXPORTS {
+        assert!(elf.lookup_dynamic_symbol(name).unwrap().is_none());
+    }
+}
@@ -0,0 +1,88 @@
+//! Decodes the notes of the files of `tests/corpus`, whose
+//! `.note.gnu.property` is padded to 8 bytes on 64 bits files and to 4 bytes
+//! on 32 bits files
+
+use elf_parser::elf::*;
+
+mod common;
+
+#[test]
+fn notes_x86_64() {
+    let elf = common::load("x86_64-cet.elf");
+    let notes = elf.notes().unwrap();
+
+    assert_eq!(notes.len(), 3);
+    assert!(notes.iter().all(|note| note.name == "GNU"));
+    assert_eq!(notes[0].n_type, NT_GNU_PROPERTY_TYPE_0);
+    assert_eq!(notes[0].offset, 0x158);
+    assert_eq!(notes[0].desc.len(), 32);
+    assert_eq!(notes[1].n_type, NT_GNU_BUILD_ID);
+    assert_eq!(notes[1].offset, 0x188);
+    assert_eq!(notes[2].n_type, NT_GNU_ABI_TAG);
+    assert_eq!(notes[2].offset, 0x1ac);
+
+    assert_eq!(elf.build_id().unwrap().unwrap(),
+               "0102030405060708090a0b0c0d0e0f1011121314");
+    assert_eq!(elf.abi_tag().unwrap(), Some(AbiTag {
+        os    : GNU_ABI_TAG_LINUX,
+        major : 3,
+        minor : 2,
+        patch : 0,
+    }));
+}
+
+#[test]
+fn properties_8_bytes_aligned() {
+    let elf = common::load("x86_64-cet.elf");
+    let features = GNU_PROPERTY_X86_FEATURE_1_IBT
+        | GNU_PROPERTY_X86_FEATURE_1_SHSTK;
+
+    // The 4 bytes of the ISA property are padded to 8 before the features
+    let properties = elf.program_properties().unwrap();
+    assert_eq!(properties, [GnuProperty::X86Isa1Needed(1),
+                            GnuProperty::X86Feature1And(features)]);
+    assert_eq!(elf.gnu_properties().unwrap(), properties);
+
+    let security = SecurityOptions::get_options(&elf).unwrap();
+    assert!(security.ibt() && security.shstk());
+    assert!(!security.bti() && !security.pac());
+}
+
+#[test]
+fn properties_aarch64() {
+    let elf = common::load("aarch64-bti.elf");
+    let features = GNU_PROPERTY_AARCH64_FEATURE_1_BTI
+        | GNU_PROPERTY_AARCH64_FEATURE_1_PAC;
+
+    assert_eq!(elf.program_properties().unwrap(),
+               [GnuProperty::AArch64Feature1And(features)]);
+
+    let security = SecurityOptions::get_options(&elf).unwrap();
+    assert!(security.bti() && security.pac());
+    assert!(!security.ibt() && !security.shstk());
+}
+
+#[test]
+fn properties_4_bytes_aligned() {
+    let elf = common::load("i386-cet.elf");
+    let features = GNU_PROPERTY_X86_FEATURE_1_IBT
+        | GNU_PROPERTY_X86_FEATURE_1_SHSTK;
+
+    // Without PT_GNU_PROPERTY the properties come from the section
+    assert!(elf.segments().iter()
+            .all(|phdr| phdr.p_type != PType::PtGnuProperty));
+    assert_eq!(elf.program_properties().unwrap(),
+               [GnuProperty::StackSize(0x100000),
+                GnuProperty::X86Feature1And(features)]);
+
+    let shdr = elf.section_by_name(".note.gnu.property").unwrap();
+    assert_eq!(shdr.sh_addralign, 4);
+    let notes = elf.notes_in(shdr.sh_offset, shdr.sh_size, 4).unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].desc.len(), 24);
+    assert_eq!(elf.gnu_note(&notes[0]).unwrap(),
+               Some(GnuNote::Properties(elf.program_properties().unwrap())));
+
+    let security = SecurityOptions::get_options(&elf).unwrap();
+    assert!(security.ibt() && security.shstk());
+}
@@ -0,0 +1,90 @@
+//! Parses the files of `tests/corpus` from memory, through `Elf::parse`,
+//! without touching the filesystem
+
+use elf_parser::elf::*;
+
+static X86_64_LSB : &[u8] = include_bytes!("corpus/x86_64-lsb.elf");
+static MIPS32_MSB : &[u8] = include_bytes!("corpus/mips32-msb.elf");
+
+#[test]
+fn parse_x86_64() {
+    let elf = Elf::parse(X86_64_LSB).unwrap();
+
+    assert_eq!(elf.class(), &EiClass::ElfClass64);
+    assert_eq!(elf.endianness(), EiData::ElfData2Lsb);
+    assert_eq!(elf.file_type(), &EType::EtExec);
+    assert_eq!(elf.machine(), &EMachine::Emx86_64);
+    assert_eq!(elf.entry(), 0x401000);
+    assert_eq!(elf.phoff(), 64);
+
+    let header = elf.header();
+    assert_eq!(header.e_phnum, 3);
+    assert_eq!(header.e_shnum, 5);
+    assert_eq!(header.e_shstrndx, 4);
+    assert_eq!(elf.segments()[0].p_type, PType::PtLoad);
+    assert_eq!(elf.sections()[2].name, ".symtab");
+
+    // Section data borrows the caller's buffer
+    let text = elf.section_by_name(".text").unwrap();
+    let data = elf.section_data(text).unwrap();
+    assert_eq!(data.as_ptr(),
+               X86_64_LSB[text.sh_offset as usize..].as_ptr());
+}
+
+#[test]
+fn parse_mips32() {
+    let elf = Elf::parse(MIPS32_MSB).unwrap();
+
+    assert_eq!(elf.class(), &EiClass::ElfClass32);
+    assert_eq!(elf.endianness(), EiData::ElfData2Msb);
+    assert_eq!(elf.machine(), &EMachine::EmMips);
+    assert_eq!(elf.entry(), 0x400100);
+    assert_eq!(elf.phoff(), 52);
+    assert_eq!(elf.header().e_ehsize, 52);
+
+    let class = ELF32::parse(MIPS32_MSB).unwrap();
+    assert_eq!(class.header.e_entry, 0x400100);
+    assert_eq!(class.pht.len(), 3);
+}
+
+#[test]
+fn parse_invalid() {
+    assert!(matches!(Elf::parse(&[]), Err(ElfError::Truncated { offset : 0 })));
+    assert!(matches!(Elf::parse(b"\x7fELG\x02"),
+                     Err(ElfError::BadMagic { .. })));
+    assert!(matches!(Elf::parse(&X86_64_LSB[..40]),
+                     Err(ElfError::Truncated { .. })));
+}
+
+#[test]
+fn parse_bad_name() {
+    // A section name out of .shstrtab leaves that name empty
+    let mut data = X86_64_LSB.to_vec();
+    let shoff = Elf::parse(X86_64_LSB).unwrap().header().e_shoff as usize;
+    data[shoff + 64..shoff + 68].copy_from_slice(&0xffffu32.to_le_bytes());
+
+    let elf = Elf::parse(&data).unwrap();
+    assert_eq!(elf.sections()[1].name, "");
+    assert_eq!(elf.sections()[2].name, ".symtab");
+}
+
+#[test]
+fn parse_small_entsize() {
+    // Program headers of 8 bytes cannot hold an Elf64_Phdr
+    let mut data = X86_64_LSB.to_vec();
+    data[0x36..0x38].copy_from_slice(&8u16.to_le_bytes());
+    assert!(matches!(Elf::parse(&data),
+                     Err(ElfError::Malformed { field : "e_phentsize", 
+                                               value : 8, .. })));
+
+    // Nor symbols of 8 bytes an Elf64_Sym
+    let mut data = X86_64_LSB.to_vec();
+    let shoff = Elf::parse(X86_64_LSB).unwrap().header().e_shoff as usize;
+    let entsize = shoff + 2 * 64 + 56;
+    data[entsize..entsize + 8].copy_from_slice(&8u64.to_le_bytes());
+    let elf = Elf::parse(&data).unwrap();
+    let symtab = elf.section_by_name(".symtab").unwrap();
+    assert!(matches!(elf.symbol_entries(symtab),
+                     Err(ElfError::Malformed { field : "sh_entsize", .. })));
+    assert!(matches!(elf.symbols(), Err(ElfError::Malformed { .. })));
+}
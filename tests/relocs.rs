@@ -0,0 +1,24 @@
+//! Decodes `SHT_RELR` words for the address sizes of the files of
+//! `tests/corpus`
+
+mod common;
+
+#[test]
+fn decode_relr_64() {
+    let elf = common::load("x86_64-lsb.elf");
+    // An address, a bitmap of the 1st and 3rd following words, a second
+    // bitmap continuing 63 words further, then a new address
+    let words = [0x10000, (0b101 << 1) | 1, (0b1 << 1) | 1, 0x20000];
+    assert_eq!(elf.decode_relr(&words),
+               [0x10000, 0x10008, 0x10018, 0x10008 + 63 * 8, 0x20000]);
+    assert!(elf.decode_relr(&[]).is_empty());
+}
+
+#[test]
+fn decode_relr_32() {
+    let elf = common::load("mips32-msb.elf");
+    // Bitmaps cover 31 words of 4 bytes on 32 bits files
+    let words = [0x1000, (0b11 << 1) | 1, (0b1 << 1) | 1];
+    assert_eq!(elf.decode_relr(&words),
+               [0x1000, 0x1004, 0x1008, 0x1004 + 31 * 4]);
+}
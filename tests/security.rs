@@ -0,0 +1,103 @@
+//! Checks the mitigations reported for the dynamically linked files of
+//! `tests/corpus`
+
+use elf_parser::elf::*;
+
+mod common;
+
+#[test]
+fn fortify_counts_fortifiable_functions() {
+    let security = common::security("x86_64-pie.elf");
+
+    // __memcpy_chk and __longjmp_chk are fortified versions of memcpy and
+    // longjmp, __fdelt_chk has no unfortified counterpart. strcpy and
+    // printf are imported unfortified.
+    assert!(security.fortify());
+    assert_eq!(security.fortified(), 2);
+    assert_eq!(security.fortifiable(), 4);
+    assert!(security.canary());
+}
+
+#[test]
+fn imports_without_section_headers() {
+    // The dynamic symbols come from DT_SYMTAB, counted by the GNU hash table
+    let elf = common::load("x86_64-pie-stripped.elf");
+    assert!(elf.sections().is_empty());
+    let names : Vec<String> = elf.imports().unwrap().into_iter()
+        .map(|sym| sym.name)
+        .collect();
+    assert_eq!(names, ["__stack_chk_fail", "__memcpy_chk", "__fdelt_chk",
+                       "__longjmp_chk", "strcpy", "printf", "memcpy"]);
+
+    let security = common::security("x86_64-pie-stripped.elf");
+    assert!(security.canary());
+    assert!(security.fortify());
+    assert_eq!((security.fortified(), security.fortifiable()), (2, 4));
+}
+
+#[test]
+fn pie_classification() {
+    let pie = |file : &str| common::security(file).pie();
+
+    assert_eq!(pie("x86_64-exec.elf"), Pie::NoPie);
+    // Marked with DF_1_PIE, or only requesting an interpreter
+    assert_eq!(pie("x86_64-pie.elf"), Pie::PieExecutable);
+    assert_eq!(pie("x86_64-legacy-pie.elf"), Pie::PieExecutable);
+    assert_eq!(pie("x86_64-static-pie.elf"), Pie::StaticPie);
+    // Has a PT_INTERP, as libc.so.6 does, and a DT_SONAME
+    assert_eq!(pie("x86_64-lib.elf"), Pie::SharedObject);
+    assert!(Pie::SharedObject.is_pie() && !Pie::NoPie.is_pie());
+}
+
+#[test]
+fn relro_levels() {
+    let relro = |file : &str| common::security(file).relro();
+
+    // PT_GNU_RELRO with DF_1_NOW in DT_FLAGS_1, or DF_BIND_NOW in DT_FLAGS
+    assert_eq!(relro("x86_64-pie.elf"), RelRo::FullRelRo);
+    assert_eq!(relro("x86_64-bind-now.elf"), RelRo::FullRelRo);
+    // PT_GNU_RELRO with lazy binding
+    assert_eq!(relro("x86_64-exec.elf"), RelRo::PartialRelRo);
+    assert_eq!(relro("x86_64-lib.elf"), RelRo::PartialRelRo);
+    // Static files have nothing to bind lazily
+    assert_eq!(relro("x86_64-lsb.elf"), RelRo::FullRelRo);
+    // No PT_GNU_RELRO
+    assert_eq!(relro("x86_64-cet.elf"), RelRo::NoRelRo);
+}
+
+#[test]
+fn relro_without_section_headers() {
+    // The flags come from the dynamic array found through PT_DYNAMIC
+    let elf = common::load("x86_64-pie-stripped.elf");
+    assert!(elf.sections().is_empty());
+    assert_eq!(common::security("x86_64-pie-stripped.elf").relro(),
+               RelRo::FullRelRo);
+}
+
+#[test]
+fn insecure_search_paths() {
+    let security = common::security("x86_64-rpath.elf");
+    let findings : Vec<(Severity, String)> = security.findings().iter()
+        .map(|finding| (finding.severity(), finding.to_string()))
+        .collect();
+
+    // /var/tmp/../lib is /var/lib, ${ORIGIN}/lib/../plugins stays below
+    // $ORIGIN and /tmpfs is not /tmp
+    assert_eq!(findings, [
+        (Severity::High, "DtRpath entry \"/tmp/build/lib\" is in a \
+                          world-writable directory".to_string()),
+        (Severity::Medium, "DtRpath entry \"$ORIGIN/../../../tmp\" is outside \
+                            of $ORIGIN, which depends on where the object is \
+                            installed".to_string()),
+        (Severity::High, "DtRunpath entry \"/dev/shm\" is in a world-writable \
+                          directory".to_string()),
+        (Severity::High, "DtRunpath entry \"lib\" is relative to the current \
+                          directory".to_string()),
+    ]);
+
+    // $ORIGIN/../lib leaves the directory of the library
+    let findings = common::security("x86_64-lib.elf").findings().to_vec();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity(), Severity::Medium);
+    assert!(common::security("x86_64-pie.elf").findings().is_empty());
+}
@@ -0,0 +1,139 @@
+//! Decodes the symbol versions of `tests/corpus/x86_64-versioned.elf`, a
+//! library defining the versions `LIBGREET_1.0` and `LIBGREET_2.0` and
+//! requiring versions of `libc.so.6` and `libgcc_s.so.1`
+
+use elf_parser::elf::*;
+
+mod common;
+
+fn load() -> Elf<'static> {
+    common::load("x86_64-versioned.elf")
+}
+
+#[test]
+fn definitions() {
+    let definitions = load().version_definitions().unwrap();
+
+    let summary : Vec<(u16, &str, bool, &[String])> = definitions.iter()
+        .map(|d| (d.index, d.name.as_str(), d.is_base(), d.parents.as_slice()))
+        .collect();
+    assert_eq!(summary, [
+        (1, "libgreet.so.2", true, &[][..]),
+        (2, "LIBGREET_1.0", false, &[][..]),
+        (3, "LIBGREET_2.0", false, &["LIBGREET_1.0".to_string()][..]),
+    ]);
+}
+
+#[test]
+fn requirements() {
+    let requirements = load().version_requirements().unwrap();
+
+    assert_eq!(requirements.len(), 2);
+    assert_eq!(requirements[0].file, "libc.so.6");
+    let libc : Vec<(&str, u16, bool)> = requirements[0].versions.iter()
+        .map(|v| (v.name.as_str(), v.index, v.is_weak()))
+        .collect();
+    assert_eq!(libc, [
+        ("GLIBC_2.2.5", 4, false),
+        ("GLIBC_2.14", 5, false),
+        ("GLIBC_2.34", 6, false),
+        ("GLIBC_PRIVATE", 7, false),
+        ("GLIBC_2.4", 8, true),
+    ]);
+    assert_eq!(requirements[1].file, "libgcc_s.so.1");
+    assert_eq!(requirements[1].versions.len(), 2);
+    assert_eq!(requirements[1].versions[1].name, "GCC_4.2.0");
+}
+
+#[test]
+fn symbol_versions() {
+    let elf = load();
+    assert_eq!(elf.version_indices().unwrap(),
+               [0, 4, 5, 6, 7, 9, 1, 2 | VERSYM_HIDDEN, 3, 2]);
+
+    // The base version and the unversioned symbols have no name
+    let names = elf.version_names().unwrap();
+    assert_eq!(names.len(), 9);
+    assert_eq!(names[&2], "LIBGREET_1.0");
+    assert_eq!(names[&9], "GCC_3.0");
+    assert!(!names.contains_key(&1));
+
+    let symbols = elf.dynamic_symbols().unwrap();
+    let versioned : Vec<String> = symbols.iter()
+        .map(Symbol::versioned_name)
+        .collect();
+    assert_eq!(versioned, [
+        "",
+        "puts@GLIBC_2.2.5",
+        "memcpy@GLIBC_2.14",
+        "__libc_start_main@GLIBC_2.34",
+        "_dl_catch_error@GLIBC_PRIVATE",
+        "_Unwind_Resume@GCC_3.0",
+        "abort",
+        "greet@LIBGREET_1.0",
+        "greet@@LIBGREET_2.0",
+        "farewell@@LIBGREET_1.0",
+    ]);
+
+    // Imports are never hidden, their version is the one they need
+    assert_eq!(symbols[1].version, Some(SymbolVersion {
+        name   : "GLIBC_2.2.5".to_string(),
+        hidden : false,
+    }));
+    assert!(symbols[7].version.as_ref().unwrap().hidden);
+    assert!(!symbols[8].version.as_ref().unwrap().hidden);
+    assert_eq!(symbols[6].version, None);
+}
+
+#[test]
+fn lookup_default_version() {
+    // The hidden greet@LIBGREET_1.0 comes first in the chain, the loader
+    // binds to greet@@LIBGREET_2.0
+    let elf = load();
+    let greet = elf.lookup_dynamic_symbol("greet").unwrap().unwrap();
+    assert_eq!(greet.versioned_name(), "greet@@LIBGREET_2.0");
+    assert_eq!(greet.value, elf.dynamic_symbols().unwrap()[8].value);
+
+    let farewell = elf.lookup_dynamic_symbol("farewell").unwrap().unwrap();
+    assert_eq!(farewell.versioned_name(), "farewell@@LIBGREET_1.0");
+    assert!(elf.lookup_dynamic_symbol("puts").unwrap().is_none());
+}
+
+#[test]
+fn max_required_versions() {
+    // GLIBC_2.34 is higher than GLIBC_2.4, GLIBC_PRIVATE has no number
+    let required = load().max_required_versions().unwrap();
+    assert_eq!(required.len(), 2);
+    assert_eq!((required[0].family.as_str(), required[0].name.as_str()),
+               ("GLIBC", "GLIBC_2.34"));
+    assert_eq!(required[0].version, [2, 34]);
+    assert_eq!(required[0].file, "libc.so.6");
+    assert_eq!(required[0].symbol.as_deref(), Some("__libc_start_main"));
+
+    // No import is bound to GCC_4.2.0
+    assert_eq!((required[1].family.as_str(), required[1].name.as_str()),
+               ("GCC", "GCC_4.2.0"));
+    assert_eq!(required[1].version, [4, 2, 0]);
+    assert_eq!(required[1].file, "libgcc_s.so.1");
+    assert_eq!(required[1].symbol, None);
+}
+
+#[test]
+fn versions_without_section_headers() {
+    // Found through DT_VERSYM, DT_VERDEF and DT_VERNEED
+    let elf = load();
+    let stripped = common::load("x86_64-versioned-stripped.elf");
+    assert!(stripped.sections().is_empty());
+
+    assert_eq!(stripped.version_indices().unwrap(),
+               elf.version_indices().unwrap());
+    assert_eq!(stripped.version_names().unwrap(),
+               elf.version_names().unwrap());
+    assert_eq!(stripped.version_requirements().unwrap().len(), 2);
+    assert_eq!(stripped.version_definitions().unwrap().len(), 3);
+
+    // The hidden greet@LIBGREET_1.0 is skipped
+    let greet = stripped.lookup_dynamic_symbol("greet").unwrap().unwrap();
+    assert_eq!(greet.versioned_name(), "greet@@LIBGREET_2.0");
+    assert_eq!(greet.value, elf.dynamic_symbols().unwrap()[8].value);
+}